@@ -1,7 +1,12 @@
 #![no_std]
 #![allow(missing_docs)]
 
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype,
+    token::{self, TokenInterface as _},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Map, String, Symbol, Vec,
+};
 
 #[cfg(test)]
 mod test;
@@ -13,7 +18,10 @@ mod test;
 /// This constant must be manually incremented with every contract upgrade
 /// (see Issue #38). External tools use this to detect logic changes at a
 /// given contract address.
-const CONTRACT_VERSION: u32 = 1;
+///
+/// Any release that changes the storage layout must also add a step to
+/// `run_migration_step` that moves data from the previous version.
+const CONTRACT_VERSION: u32 = 4;
 
 // ── Data Types ──────────────────────────────────────────────────────────────
 
@@ -37,6 +45,154 @@ pub enum Status {
 pub struct RoadmapItem {
     pub date: u64,
     pub description: String,
+    /// Share of the raised funds held in escrow for this milestone, in basis
+    /// points. Zero for purely informational roadmap items.
+    pub payout_bps: u32,
+    /// Whether the milestone's completion has been approved.
+    pub approved: bool,
+    /// Whether the milestone's tranche has been paid to the creator.
+    pub released: bool,
+}
+
+/// Roadmap item layout used before storage version 3.
+#[derive(Clone)]
+#[contracttype]
+struct RoadmapItemV2 {
+    pub date: u64,
+    pub description: String,
+}
+
+/// Rules for `withdraw_contribution`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WithdrawalTerms {
+    /// Seconds before the deadline during which withdrawals are blocked.
+    pub lock_period: u64,
+    /// Share of each withdrawal, in basis points, paid to the creator.
+    pub penalty_bps: u32,
+}
+
+/// What a backer proposal does when it passes.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum ProposalAction {
+    /// Approve the escrowed milestone at this roadmap index for release.
+    ApproveMilestone(u32),
+    /// Move the deadline of an Active campaign to this timestamp.
+    ExtendDeadline(u64),
+    /// Refund an Active campaign, or abandon the escrow or halt the payout
+    /// stream of a successful one.
+    ForceRefund,
+}
+
+/// A backer proposal and its running tally.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Proposal {
+    pub id: u32,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    /// Voting closes after this timestamp.
+    pub end: u64,
+    /// Total voting weight when the proposal was created.
+    pub total_weight: i128,
+    pub votes_for: i128,
+    pub votes_against: i128,
+    pub executed: bool,
+}
+
+/// Escrow state for the `escrow_info` view.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct EscrowInfo {
+    /// Sum of `payout_bps` across all milestones.
+    pub total_bps: u32,
+    /// Total raised at the time of `withdraw`; tranches are sized against it.
+    pub base: i128,
+    /// Amount held back for milestones at `withdraw`.
+    pub held: i128,
+    /// Amount released to the creator so far (before fees).
+    pub released: i128,
+    /// Whether the escrow was abandoned and opened for pro-rata refunds.
+    pub abandoned: bool,
+}
+
+/// Schedule for streaming the creator's payout after `withdraw`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VestingTerms {
+    /// Seconds after `withdraw` before anything vests.
+    pub cliff: u64,
+    /// Seconds after `withdraw` until the whole payout has vested.
+    pub duration: u64,
+}
+
+/// A backer's voting weight before its first change while `ProposalCount`
+/// stood at `epoch`, i.e. their weight when proposal `epoch - 1` was created.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WeightCheckpoint {
+    pub epoch: u32,
+    pub weight: i128,
+}
+
+/// The creator's payout stream, started by `withdraw`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VestingStream {
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    /// Campaign-token total raised at `withdraw`; refunds are sized against it.
+    pub base: i128,
+    /// Net creator payout being streamed.
+    pub total: i128,
+    pub claimed: i128,
+    /// When the stream was halted, if it was.
+    pub halted_at: Option<u64>,
+}
+
+/// Stream balances for the `vesting_info` view.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VestingInfo {
+    /// Amount vested so far, including what was claimed.
+    pub vested: i128,
+    pub claimed: i128,
+    /// Amount not yet vested; refundable once the stream is halted.
+    pub locked: i128,
+    pub halted: bool,
+}
+
+/// Upper bound on what a single address may contribute and pledge.
+///
+/// Either bound may be 0 to leave it unset; when both are set the lower one
+/// applies.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ContributionLimit {
+    /// Absolute cap in goal units.
+    pub max_amount: i128,
+    /// Cap as a share of the goal, in basis points.
+    pub max_goal_bps: u32,
+}
+
+/// Raw and goal-unit amounts of one secondary token.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TokenBalance {
+    /// Amount in the secondary token's own units.
+    pub amount: i128,
+    /// Value in goal units at the prices used when it was contributed.
+    pub value: i128,
+}
+
+/// Interface the campaign expects from a price-feed contract.
+#[contractclient(name = "PriceFeedClient")]
+pub trait PriceFeed {
+    /// Returns the value of one unit of `token` in goal units, scaled by
+    /// `PRICE_SCALE`.
+    fn price(env: Env, token: Address) -> i128;
 }
 
 /// Platform configuration for fee handling.
@@ -47,12 +203,263 @@ pub struct PlatformConfig {
     pub fee_bps: u32,
 }
 
+/// A fee rate that applies once the campaign has raised `threshold`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeTier {
+    pub threshold: i128,
+    pub fee_bps: u32,
+}
+
+/// One recipient's cut of the platform fee.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeRecipient {
+    pub address: Address,
+    /// Share of the fee in basis points; shares total 10,000.
+    pub share_bps: u32,
+}
+
+/// The platform fee charged on creator payouts.
+///
+/// The rate is `fee_bps`, or the rate of the highest tier whose threshold
+/// the campaign reached. `min_fee` and `max_fee` bound the total fee charged
+/// in the campaign token across all payouts; `max_fee` of 0 means no cap.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeSchedule {
+    pub fee_bps: u32,
+    /// Ordered by ascending threshold.
+    pub tiers: Vec<FeeTier>,
+    pub min_fee: i128,
+    pub max_fee: i128,
+    pub recipients: Vec<FeeRecipient>,
+}
+
+/// One recipient's amount in a fee preview.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeShare {
+    pub address: Address,
+    pub amount: i128,
+}
+
+/// Result of the `preview_fees` view.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeePreview {
+    pub fee_bps: u32,
+    pub fee: i128,
+    pub creator_payout: i128,
+    pub shares: Vec<FeeShare>,
+}
+
+/// Named permissions for privileged campaign operations.
+///
+/// `Owner` satisfies every role check and is the only role that can grant or
+/// revoke roles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum Role {
+    /// Full control, including role management and deadline changes.
+    Owner,
+    /// Maintains metadata, roadmap, reward tiers and stretch goals.
+    Editor,
+    /// Pauses and unpauses the campaign.
+    Pauser,
+    /// Manages financial settings such as the hard cap policy.
+    Treasurer,
+}
+
+/// A contract upgrade announced via `schedule_upgrade`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingUpgrade {
+    /// The SHA-256 hash of the new WASM binary.
+    pub wasm_hash: soroban_sdk::BytesN<32>,
+    /// Earliest ledger timestamp at which the upgrade may be executed.
+    pub eta: u64,
+}
+
+/// Where a pledger's pledge stands.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum PledgeStatus {
+    /// Recorded and waiting for collection.
+    Pending,
+    /// Pulled in as a contribution.
+    Collected,
+    /// The pull failed at collection, so the pledge was dropped.
+    Defaulted,
+    /// Only part was pulled in; the hard cap left no room for the rest.
+    PartiallyCollected,
+    /// Nothing was pulled in because the hard cap had already been reached.
+    CappedOut,
+}
+
+/// A pledge the hard cap cut short at collection.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CappedPledge {
+    /// Amount pulled in as a contribution.
+    pub collected: i128,
+    /// Amount left with the pledger.
+    pub uncollected: i128,
+}
+
+/// How `contribute` handles an amount that would push the total past the
+/// hard cap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum HardCapPolicy {
+    /// Accept only the remaining headroom and leave the rest with the contributor.
+    Truncate,
+    /// Reject the whole contribution with `HardCapExceeded`.
+    Reject,
+    /// Accept the full amount and close the campaign immediately.
+    AcceptAndClose,
+}
+
 /// A reward tier with a name and minimum contribution amount to qualify.
-#[derive(Clone)]
+///
+/// Limited tiers have a `supply` and are reserved explicitly with
+/// `contribute_for_tier`; the remaining supply is `supply - claimed`.
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct RewardTier {
     pub name: String,
     pub min_amount: i128,
+    /// Number of slots available, or None for an unlimited tier.
+    pub supply: Option<u32>,
+    /// Number of slots reserved so far.
+    pub claimed: u32,
+    /// Ledger timestamp from which the tier can be selected, if restricted.
+    pub opens_at: Option<u64>,
+    /// Ledger timestamp after which the tier can no longer be selected.
+    pub closes_at: Option<u64>,
+}
+
+/// Delivery state of a backer's reward after a successful campaign.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum FulfillmentStatus {
+    /// Not yet shipped.
+    Pending,
+    /// Shipped by the creator, with a hash of the shipment proof.
+    Shipped(BytesN<32>),
+    /// Receipt confirmed by the backer.
+    Confirmed,
+    /// The backer reported a problem with delivery.
+    Disputed,
+}
+
+/// Number of backers in each fulfillment state for one reward tier.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FulfillmentSummary {
+    pub pending: u32,
+    pub shipped: u32,
+    pub confirmed: u32,
+    pub disputed: u32,
+}
+
+/// Name and symbol of the campaign share token.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ShareTokenInfo {
+    pub name: String,
+    pub symbol: String,
+}
+
+/// A share-token allowance and the ledger it expires at.
+#[derive(Clone)]
+#[contracttype]
+struct ShareAllowance {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+/// A sponsor's matching pool.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct MatchingPool {
+    pub sponsor: Address,
+    /// Match per contributed unit, in basis points (10,000 = 1:1).
+    pub ratio_bps: u32,
+    /// Most any one backer can have matched, or 0 for no limit.
+    pub per_backer_cap: i128,
+    /// Total deposited by the sponsor.
+    pub deposited: i128,
+    /// Amount currently committed as matches.
+    pub matched: i128,
+    /// Whether the matched amount went back to the sponsor after a failure.
+    pub returned: bool,
+    /// Whether the sponsor reclaimed the unused balance.
+    pub reclaimed: bool,
+}
+
+/// A backer's recurring contribution, pulled from a token allowance.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Subscription {
+    pub amount: i128,
+    /// Seconds between payments.
+    pub interval: u64,
+    /// Earliest time the next payment can be pulled.
+    pub next_due: u64,
+    /// Payments collected so far.
+    pub payments: u32,
+    /// Pulls that were skipped because they failed.
+    pub failed_pulls: u32,
+    /// Whether the most recent pull failed.
+    pub last_failed: bool,
+}
+
+/// Which referrer a contributor is attributed to, and how much of their
+/// contribution came in through the referral.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReferralRecord {
+    pub referrer: Address,
+    pub amount: i128,
+}
+
+/// One row of the referral leaderboard.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReferralStats {
+    pub referrer: Address,
+    /// Contributions currently attributed to the referrer.
+    pub referred: i128,
+    /// Bonus paid to the referrer at `withdraw`.
+    pub earned: i128,
+}
+
+/// Non-fungible record of a single contribution.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Receipt {
+    pub id: u64,
+    /// Current holder; starts as the contributor and changes on transfer.
+    pub owner: Address,
+    /// The campaign contract that issued the receipt.
+    pub campaign: Address,
+    /// Amount contributed, in goal units, less any share later withdrawn.
+    pub amount: i128,
+    /// Reward tier the contributor held when the receipt was minted.
+    pub tier: Option<u32>,
+    /// Ledger timestamp of the contribution.
+    pub timestamp: u64,
+    /// Whether the contribution behind the receipt was returned.
+    pub void: bool,
+}
+
+/// Reward tier layout used before storage version 4.
+#[derive(Clone)]
+#[contracttype]
+struct RewardTierV3 {
+    pub name: String,
+    pub min_amount: i128,
 }
 
 /// Represents all storage keys used by the crowdfund contract.
@@ -71,9 +478,16 @@ pub struct CampaignStats {
     pub average_contribution: i128,
     /// Largest contribution amount.
     pub largest_contribution: i128,
+    /// Raw amount raised per token, including the campaign token.
+    pub raised_by_token: Map<Address, i128>,
 }
 
 /// Represents all storage keys used by the crowdfund contract.
+///
+/// Subsystem keys live in the enums that follow, since a `#[contracttype]`
+/// enum is limited to 50 cases. A key is encoded by its variant name and
+/// fields only, so a variant keeps addressing the same entry when moved, and
+/// variant names must stay unique across all key enums.
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -81,6 +495,8 @@ pub enum DataKey {
     Paused,
     /// The hard cap for the campaign.
     HardCap,
+    /// How contributions that overshoot the hard cap are handled.
+    HardCapPolicy,
     /// The campaign category.
     Category,
     /// The campaign tags.
@@ -107,6 +523,50 @@ pub enum DataKey {
     Roadmap,
     /// The address authorized to upgrade the contract.
     Admin,
+    /// Admin proposed via `propose_admin`, awaiting `accept_admin`.
+    PendingAdmin,
+    /// Upgrade announced via `schedule_upgrade`, awaiting `execute_upgrade`.
+    PendingUpgrade,
+    /// Version of the storage layout; missing for pre-migration campaigns.
+    StorageVersion,
+    /// Whether an address holds a role.
+    Role(Role, Address),
+    /// Number of addresses holding the `Owner` role.
+    OwnerCount,
+    /// Sum of `payout_bps` across roadmap milestones.
+    EscrowBps,
+    /// Total raised when `withdraw` moved the campaign into escrow.
+    EscrowBase,
+    /// Escrowed amount released to the creator so far.
+    EscrowReleased,
+    /// Whether the escrow was abandoned and is refundable pro-rata.
+    EscrowAbandoned,
+    /// Number of proposals created; also the next proposal id.
+    ProposalCount,
+    /// Proposal by id.
+    Proposal(u32),
+    /// A backer's vote on a proposal (true = for).
+    Vote(u32, Address),
+    /// A backer's voting weight before each change since proposals began.
+    WeightCheckpoints(Address),
+    /// Lock period and penalty for `withdraw_contribution`.
+    WithdrawalTerms,
+    /// Secondary tokens accepted through `contribute_token`.
+    AcceptedTokens,
+    /// Price-feed contract used to value secondary tokens.
+    PriceFeed,
+    /// A contributor's holding of one secondary token.
+    TokenContribution(Address, Address),
+    /// Campaign-wide holding of one secondary token.
+    TokenRaised(Address),
+    /// Merkle root of the contribution allowlist; absent when ungated.
+    AllowlistRoot,
+    /// Per-address maximum on contributions plus pledges.
+    ContributionLimit,
+    /// Index of the reward tier a contributor reserved.
+    SelectedTier(Address),
+    /// Reward fulfillment state per backer; absent means Pending.
+    Fulfillment(Address),
     /// Campaign title.
     Title,
     /// Last contribution timestamp per address (for rate limiting).
@@ -115,27 +575,166 @@ pub enum DataKey {
     Description,
     /// Campaign social links.
     SocialLinks,
-    /// Platform configuration for fee handling.
-    PlatformConfig,
     /// List of reward tiers (name + min_amount).
     RewardTiers,
+    /// List of stretch goal milestones.
+    StretchGoals,
+    /// Whether a contributor has already claimed their refund.
+    RefundClaimed(Address),
+    /// Index of the next contributor to process in `refund_batch`.
+    RefundCursor,
+}
+
+/// Storage keys for contribution receipts.
+#[derive(Clone)]
+#[contracttype]
+pub enum ReceiptKey {
+    /// Number of receipts minted; also the next receipt id.
+    ReceiptCount,
+    /// Contribution receipt by id.
+    Receipt(u64),
+    /// Ids of receipts currently held by an address.
+    ReceiptsOf(Address),
+    /// Ids of receipts minted for an address's contributions.
+    ReceiptsMinted(Address),
+}
+
+/// Storage keys for the campaign share token.
+#[derive(Clone)]
+#[contracttype]
+pub enum ShareKey {
+    /// Share token metadata; present only when share mode is enabled.
+    Metadata,
+    /// Share token balance by address.
+    Balance(Address),
+    /// Share token allowance by (owner, spender).
+    Allowance(Address, Address),
+    /// Total share token supply.
+    Supply,
+    /// Every address that has held shares, in the order it first did.
+    Holders,
+}
+
+/// Storage keys for referral attribution and bonuses.
+#[derive(Clone)]
+#[contracttype]
+pub enum ReferralKey {
+    /// Referral bonus, in basis points of referred contributions.
+    ReferralBps,
+    /// A contributor's referral attribution.
+    Referral(Address),
+    /// Contributions currently attributed to a referrer.
+    ReferredTotal(Address),
+    /// Referral bonus paid to a referrer.
+    ReferralEarnings(Address),
+    /// Every address that has referred a contribution.
+    Referrers,
+    /// The top referrers by attributed contributions, highest first.
+    Leaderboard,
+}
+
+/// Storage keys for sponsor matching and outside grants.
+#[derive(Clone)]
+#[contracttype]
+pub enum MatchingKey {
+    /// The sponsor matching pool, if one was funded.
+    MatchingPool,
+    /// Matching funds committed against a contributor.
+    Matched(Address),
+    /// Outside grants received, keyed by funder.
+    Grants,
+    /// Addresses allowed to send grants, such as a funding round's factory.
+    GrantSources,
+}
+
+/// Storage keys for recurring contributions.
+#[derive(Clone)]
+#[contracttype]
+pub enum SubscriptionKey {
+    /// A backer's recurring contribution.
+    Subscription(Address),
+    /// Every address that has subscribed. Cancelled subscriptions keep
+    /// their slot so `process_subscriptions` pages never shift.
+    Subscribers,
+}
+
+/// Storage keys for the vested creator payout.
+#[derive(Clone)]
+#[contracttype]
+pub enum VestingKey {
+    /// Vesting schedule for the creator payout.
+    VestingTerms,
+    /// The creator payout stream, once `withdraw` has run.
+    Vesting,
+    /// Whether a contributor has claimed their share of a halted stream.
+    VestingRefundClaimed(Address),
+}
+
+/// Storage keys for the platform fee.
+#[derive(Clone)]
+#[contracttype]
+pub enum FeeKey {
+    /// Platform fee schedule.
+    PlatformConfig,
+    /// Platform fees charged in the campaign token so far.
+    FeesCharged,
+}
+
+/// Storage keys for pledges and their collection.
+#[derive(Clone)]
+#[contracttype]
+pub enum PledgeKey {
     /// Individual pledge by address.
     Pledge(Address),
     /// List of all pledger addresses.
     Pledgers,
     /// Total amount pledged (not yet collected).
     TotalPledged,
-    /// List of stretch goal milestones.
-    StretchGoals,
+    /// Index of the next pledger to process in `collect_pledges_batch`.
+    PledgeCursor,
+    /// Pledge amount that could not be collected, by pledger.
+    PledgeDefault(Address),
+    /// Total pledge amount that could not be collected.
+    TotalDefaulted,
+    /// Pledge cut short by the hard cap at collection, by pledger.
+    PledgeCapped(Address),
+    /// Pledge amount collected so far but not yet in `TotalRaised`.
+    TotalCollected,
+    /// Seconds before the deadline during which pledges cannot change.
+    PledgeLockWindow,
 }
 
+// ── Upgrade Timelock ───────────────────────────────────────────────────────
+/// Minimum notice, in seconds, between scheduling and executing an upgrade.
+const UPGRADE_DELAY: u64 = 7 * 24 * 60 * 60;
+
+// ── Milestone Escrow ───────────────────────────────────────────────────────
+/// Seconds after a milestone's date during which the creator may still
+/// release it before anyone can declare the escrow abandoned.
+const MILESTONE_GRACE_PERIOD: u64 = 90 * 24 * 60 * 60;
+
+// ── Backer Governance ──────────────────────────────────────────────────────
+/// Length of a proposal's voting window in seconds.
+const VOTING_PERIOD: u64 = 3 * 24 * 60 * 60;
+/// Share of the total voting weight, in basis points, that must vote for a
+/// proposal to count.
+const PROPOSAL_QUORUM_BPS: i128 = 2_000;
+
+// ── Multi-Token Contributions ──────────────────────────────────────────────
+/// Fixed-point scale of prices returned by the price feed (7 decimals).
+const PRICE_SCALE: i128 = 10_000_000;
+
+// ── Referrals ──────────────────────────────────────────────────────────────
+/// Most referrers kept on the referral leaderboard.
+const LEADERBOARD_SIZE: u32 = 10;
+
 // ── Rate Limiting ──────────────────────────────────────────────────────────
 /// Minimum seconds required between contributions from the same address.
 const CONTRIBUTION_COOLDOWN: u64 = 5;
 
 // ── Contract Error ──────────────────────────────────────────────────────────
 
-use soroban_sdk::contracterror;
+use soroban_sdk::{contracterror, panic_with_error};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -151,6 +750,42 @@ pub enum ContractError {
     HardCapExceeded = 8,
     RateLimitExceeded = 9,
     ContractPaused = 10,
+    NotRefundable = 11,
+    AlreadyClaimed = 12,
+    NothingToClaim = 13,
+    InvalidCursor = 14,
+    SettlementInProgress = 15,
+    NoPendingUpgrade = 16,
+    MigrationPending = 17,
+    NoMigrationPending = 18,
+    MilestoneNotReady = 19,
+    EscrowNotAbandoned = 20,
+    NotABacker = 21,
+    AlreadyVoted = 22,
+    VotingClosed = 23,
+    VotingOpen = 24,
+    ProposalRejected = 25,
+    WithdrawalLocked = 26,
+    InsufficientContribution = 27,
+    UnsupportedToken = 28,
+    InvalidPrice = 29,
+    AllowlistActive = 30,
+    AllowlistInactive = 31,
+    InvalidProof = 32,
+    AllowlistCapExceeded = 33,
+    ContributionLimitExceeded = 34,
+    TierNotFound = 35,
+    TierUnavailable = 36,
+    TierSoldOut = 37,
+    SelfReferral = 38,
+    InsufficientAllowance = 39,
+    PaymentFailed = 40,
+    NoSubscription = 41,
+    VestingNotHalted = 42,
+    PledgeLocked = 43,
+    NoPledge = 44,
+    NoVotingWeight = 45,
+    ReferrerMismatch = 46,
 }
 
 // ── Contract ────────────────────────────────────────────────────────────────
@@ -170,10 +805,14 @@ impl CrowdfundContract {
     /// * `hard_cap`           – Maximum total amount that can be raised (must be >= goal).
     /// * `deadline`           – The campaign deadline as a ledger timestamp.
     /// * `min_contribution`   – The minimum contribution amount.
-    /// * `platform_config`    – Optional platform configuration (address and fee in basis points).
+    /// * `platform_config`    – Optional platform configuration (address and fee in basis points);
+    ///   stored as a single-recipient [`FeeSchedule`].
+    ///
+    /// # Errors
+    /// * `AlreadyInitialized` – The campaign was already initialized.
+    /// * `InvalidHardCap`     – `hard_cap` is below `goal`.
     ///
     /// # Panics
-    /// * If already initialized.
     /// * If platform fee exceeds 10,000 (100%).
     #[allow(clippy::too_many_arguments)]
     pub fn initialize(
@@ -181,7 +820,7 @@ impl CrowdfundContract {
         creator: Address,
         token: Address,
         goal: i128,
-        _hard_cap: i128,
+        hard_cap: i128,
         deadline: u64,
         min_contribution: i128,
         platform_config: Option<PlatformConfig>,
@@ -193,6 +832,10 @@ impl CrowdfundContract {
 
         creator.require_auth();
 
+        if hard_cap < goal {
+            return Err(ContractError::InvalidHardCap);
+        }
+
         // Validate platform fee if provided.
         if let Some(ref config) = platform_config {
             if config.fee_bps > 10_000 {
@@ -201,9 +844,18 @@ impl CrowdfundContract {
         }
 
         env.storage().instance().set(&DataKey::Creator, &creator);
+        env.storage().instance().set(&DataKey::Admin, &creator);
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(Role::Owner, creator.clone()), &true);
+        env.storage().instance().set(&DataKey::OwnerCount, &1u32);
         env.storage().instance().set(&DataKey::Token, &token);
 
         env.storage().instance().set(&DataKey::Goal, &goal);
+        env.storage().instance().set(&DataKey::HardCap, &hard_cap);
+        env.storage()
+            .instance()
+            .set(&DataKey::HardCapPolicy, &HardCapPolicy::Truncate);
         env.storage().instance().set(&DataKey::Deadline, &deadline);
         env.storage()
             .instance()
@@ -213,6 +865,28 @@ impl CrowdfundContract {
             .instance()
             .set(&DataKey::Status, &Status::Active);
         env.storage().instance().set(&DataKey::Paused, &false);
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &CONTRACT_VERSION);
+
+        if let Some(config) = platform_config {
+            let schedule = FeeSchedule {
+                fee_bps: config.fee_bps,
+                tiers: Vec::new(&env),
+                min_fee: 0,
+                max_fee: 0,
+                recipients: Vec::from_array(
+                    &env,
+                    [FeeRecipient {
+                        address: config.address,
+                        share_bps: 10_000,
+                    }],
+                ),
+            };
+            env.storage()
+                .instance()
+                .set(&FeeKey::PlatformConfig, &schedule);
+        }
 
         let empty_contributors: Vec<Address> = Vec::new(&env);
         env.storage()
@@ -235,27 +909,141 @@ impl CrowdfundContract {
     /// Contribute tokens to the campaign.
     ///
     /// The contributor must authorize the call. Contributions are rejected
-    /// after the deadline has passed.
-    pub fn contribute(env: Env, contributor: Address, amount: i128) -> Result<(), ContractError> {
-        // ── Rate limiting: enforce cooldown between contributions ──
-        let now = env.ledger().timestamp();
-        let last_time_key = DataKey::LastContributionTime(contributor.clone());
-        if let Some(last_time) = env.storage().persistent().get::<_, u64>(&last_time_key) {
-            if now < last_time + CONTRIBUTION_COOLDOWN {
-                return Err(ContractError::RateLimitExceeded);
+    /// after the deadline has passed. Amounts that would exceed the hard cap
+    /// are handled according to the campaign's [`HardCapPolicy`].
+    ///
+    /// Returns the amount actually transferred, which may be less than
+    /// `amount` under `HardCapPolicy::Truncate`.
+    ///
+    /// # Errors
+    /// * `AllowlistActive` – The campaign is gated; use `contribute_with_proof`.
+    pub fn contribute(env: Env, contributor: Address, amount: i128) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
+
+        let effective_amount = accept_contribution(&env, contributor.clone(), amount, false)?;
+        mint_shares(&env, &contributor, effective_amount);
+        mint_receipt(&env, &contributor, effective_amount);
+        Ok(effective_amount)
+    }
+
+    /// Contribute on behalf of a referrer, who earns the campaign's referral
+    /// bonus on the amount if the campaign succeeds.
+    ///
+    /// Attribution is first-touch: once a contributor has been referred,
+    /// later referred contributions must name the same referrer.
+    ///
+    /// Returns the amount actually transferred, as for `contribute`.
+    ///
+    /// # Errors
+    /// * `SelfReferral`     – `referrer` is the contributor.
+    /// * `ReferrerMismatch` – The contributor was already referred by someone else.
+    pub fn contribute_with_referrer(
+        env: Env,
+        contributor: Address,
+        amount: i128,
+        referrer: Address,
+    ) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        if referrer == contributor {
+            return Err(ContractError::SelfReferral);
+        }
+        let referral_key = ReferralKey::Referral(contributor.clone());
+        let existing: Option<ReferralRecord> = env.storage().persistent().get(&referral_key);
+        if existing
+            .as_ref()
+            .is_some_and(|record| record.referrer != referrer)
+        {
+            return Err(ContractError::ReferrerMismatch);
+        }
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
+
+        let effective_amount = accept_contribution(&env, contributor.clone(), amount, false)?;
+        mint_shares(&env, &contributor, effective_amount);
+        mint_receipt(&env, &contributor, effective_amount);
+
+        let mut referral = existing.unwrap_or(ReferralRecord {
+            referrer,
+            amount: 0,
+        });
+        referral.amount = referral
+            .amount
+            .checked_add(effective_amount)
+            .ok_or(ContractError::Overflow)?;
+        env.storage().persistent().set(&referral_key, &referral);
+        env.storage()
+            .persistent()
+            .extend_ttl(&referral_key, 100, 100);
+
+        let total_key = ReferralKey::ReferredTotal(referral.referrer.clone());
+        let referred: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+        if referred == 0 {
+            let mut referrers = Self::referrers(env.clone());
+            if !referrers.contains(&referral.referrer) {
+                referrers.push_back(referral.referrer.clone());
+                env.storage()
+                    .persistent()
+                    .set(&ReferralKey::Referrers, &referrers);
+                env.storage()
+                    .persistent()
+                    .extend_ttl(&ReferralKey::Referrers, 100, 100);
             }
         }
+        let new_referred = referred
+            .checked_add(effective_amount)
+            .ok_or(ContractError::Overflow)?;
+        env.storage().persistent().set(&total_key, &new_referred);
+        env.storage().persistent().extend_ttl(&total_key, 100, 100);
+        rank_referrer(&env, &referral.referrer, new_referred);
 
-        let paused: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Paused)
-            .unwrap_or(false);
-        if paused {
-            return Err(ContractError::ContractPaused);
+        env.events().publish(
+            ("campaign", "referred"),
+            (contributor, referral.referrer, effective_amount),
+        );
+
+        Ok(effective_amount)
+    }
+
+    /// Set up a recurring contribution of `amount` every `interval` seconds.
+    ///
+    /// Payments are pulled by [`process_subscriptions`](Self::process_subscriptions)
+    /// from a token allowance the backer grants this contract; the first one
+    /// is due immediately. Subscribing again replaces the amount and interval
+    /// but keeps the schedule.
+    ///
+    /// # Errors
+    /// * `AllowlistActive`       – The campaign is gated by an allowlist.
+    /// * `CampaignEnded`         – The deadline has passed.
+    /// * `InsufficientAllowance` – The allowance does not cover one payment.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If `amount` is below the minimum contribution or `interval` is zero.
+    pub fn subscribe(
+        env: Env,
+        backer: Address,
+        amount: i128,
+        interval: u64,
+    ) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
         }
 
-        contributor.require_auth();
+        backer.require_auth();
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
 
         let min_contribution: i128 = env
             .storage()
@@ -265,66 +1053,431 @@ impl CrowdfundContract {
         if amount < min_contribution {
             panic!("amount below minimum");
         }
+        if interval == 0 {
+            panic!("invalid subscription interval");
+        }
 
+        let now = env.ledger().timestamp();
         let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
-        if env.ledger().timestamp() > deadline {
+        if now > deadline {
             return Err(ContractError::CampaignEnded);
         }
 
-        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
-        let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
-
-        if total >= hard_cap {
-            return Err(ContractError::HardCapExceeded);
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let allowance = token::Client::new(&env, &token_address)
+            .allowance(&backer, &env.current_contract_address());
+        if allowance < amount {
+            return Err(ContractError::InsufficientAllowance);
         }
 
-        let headroom = hard_cap - total;
-        let effective_amount = if amount <= headroom { amount } else { headroom };
+        let key = SubscriptionKey::Subscription(backer.clone());
+        let existing: Option<Subscription> = env.storage().persistent().get(&key);
+        let subscription = match existing {
+            Some(existing) => Subscription {
+                amount,
+                interval,
+                ..existing
+            },
+            None => {
+                let mut subscribers = Self::subscribers(env.clone());
+                if !subscribers.contains(&backer) {
+                    subscribers.push_back(backer.clone());
+                    env.storage()
+                        .persistent()
+                        .set(&SubscriptionKey::Subscribers, &subscribers);
+                    env.storage()
+                        .persistent()
+                        .extend_ttl(&SubscriptionKey::Subscribers, 100, 100);
+                }
+                Subscription {
+                    amount,
+                    interval,
+                    next_due: now,
+                    payments: 0,
+                    failed_pulls: 0,
+                    last_failed: false,
+                }
+            }
+        };
+        env.storage().persistent().set(&key, &subscription);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
 
-        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
-        let token_client = token::Client::new(&env, &token_address);
+        env.events()
+            .publish(("campaign", "subscribed"), (backer, amount, interval));
 
-        // Transfer tokens from the contributor to this contract.
-        token_client.transfer(
-            &contributor,
-            &env.current_contract_address(),
-            &effective_amount,
-        );
+        Ok(())
+    }
 
-        // Update the contributor's running total with overflow protection.
-        let contribution_key = DataKey::Contribution(contributor.clone());
-        let prev: i128 = env
-            .storage()
-            .persistent()
-            .get(&contribution_key)
+    /// Stop a recurring contribution. Payments already collected stay
+    /// contributed; the backer may also revoke the token allowance.
+    ///
+    /// The backer keeps their slot in `subscribers`, so keepers paging
+    /// through the list never skip anyone, and reuses it on resubscribing.
+    ///
+    /// # Errors
+    /// * `NoSubscription` – The backer has no subscription.
+    pub fn cancel_subscription(env: Env, backer: Address) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        backer.require_auth();
+
+        let key = SubscriptionKey::Subscription(backer.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(ContractError::NoSubscription);
+        }
+        env.storage().persistent().remove(&key);
+
+        env.events()
+            .publish(("campaign", "subscription_cancelled"), backer);
+
+        Ok(())
+    }
+
+    /// Collect due subscription payments for one page of subscribers —
+    /// callable by anyone.
+    ///
+    /// Processes `SubscriptionKey::Subscribers[start..start + limit]`,
+    /// skipping cancelled slots. Each due payment goes through the same
+    /// accounting as `contribute`, except that scheduled pulls are exempt from
+    /// the contribution cooldown. A pull that
+    /// fails (revoked allowance, empty balance, hard cap, an allowlist set
+    /// after subscribing, ...) is skipped and flagged on the subscription. Either way the subscription moves to its
+    /// next slot after now; missed periods are not charged later.
+    ///
+    /// Returns the number of payments collected.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    pub fn process_subscriptions(env: Env, start: u32, limit: u32) -> Result<u32, ContractError> {
+        require_migrated(&env);
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        let subscribers = Self::subscribers(env.clone());
+        let end = start.saturating_add(limit).min(subscribers.len());
+        let now = env.ledger().timestamp();
+        let gated = env.storage().instance().has(&DataKey::AllowlistRoot);
+
+        let mut collected = 0u32;
+        for i in start..end {
+            let backer = subscribers.get(i).unwrap();
+            let key = SubscriptionKey::Subscription(backer.clone());
+            let subscription: Option<Subscription> = env.storage().persistent().get(&key);
+            let Some(mut subscription) = subscription else {
+                continue;
+            };
+            if subscription.next_due > now {
+                continue;
+            }
+
+            let periods = (now - subscription.next_due) / subscription.interval + 1;
+            subscription.next_due = subscription
+                .next_due
+                .saturating_add(periods.saturating_mul(subscription.interval));
+
+            // Subscribers proved nothing against the allowlist, so they
+            // cannot be charged while one is in force.
+            let result = if gated {
+                Err(ContractError::AllowlistActive)
+            } else {
+                accept_contribution(&env, backer.clone(), subscription.amount, true)
+            };
+            match result {
+                Ok(amount) => {
+                    mint_shares(&env, &backer, amount);
+                    mint_receipt(&env, &backer, amount);
+                    subscription.payments += 1;
+                    subscription.last_failed = false;
+                    collected += 1;
+                }
+                Err(err) => {
+                    subscription.failed_pulls += 1;
+                    subscription.last_failed = true;
+                    env.events().publish(
+                        ("campaign", "subscription_failed"),
+                        (backer, subscription.amount, err as u32),
+                    );
+                }
+            }
+
+            env.storage().persistent().set(&key, &subscription);
+            env.storage().persistent().extend_ttl(&key, 100, 100);
+        }
+
+        Ok(collected)
+    }
+
+    /// Contribute while the campaign is gated by an allowlist.
+    ///
+    /// The allowlist leaf for a participant is
+    /// `sha256(contributor.to_xdr() || cap.to_be_bytes())`, and `proof` lists
+    /// the sibling hashes from that leaf up to the root. Pairs are hashed in
+    /// sorted order. `cap` limits the contributor's running total.
+    ///
+    /// Returns the amount actually transferred, as for `contribute`.
+    ///
+    /// # Errors
+    /// * `AllowlistInactive`    – No allowlist root is set.
+    /// * `InvalidProof`         – The leaf is not in the allowlist.
+    /// * `AllowlistCapExceeded` – The contribution would pass `cap`.
+    pub fn contribute_with_proof(
+        env: Env,
+        contributor: Address,
+        amount: i128,
+        cap: i128,
+        proof: Vec<BytesN<32>>,
+    ) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let root: BytesN<32> = env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowlistRoot)
+            .ok_or(ContractError::AllowlistInactive)?;
+
+        let mut leaf = contributor.clone().to_xdr(&env);
+        leaf.extend_from_array(&cap.to_be_bytes());
+        let mut node: BytesN<32> = env.crypto().sha256(&leaf).into();
+        for sibling in proof.iter() {
+            let mut pair = Bytes::new(&env);
+            if node < sibling {
+                pair.extend_from_array(&node.to_array());
+                pair.extend_from_array(&sibling.to_array());
+            } else {
+                pair.extend_from_array(&sibling.to_array());
+                pair.extend_from_array(&node.to_array());
+            }
+            node = env.crypto().sha256(&pair).into();
+        }
+        if node != root {
+            return Err(ContractError::InvalidProof);
+        }
+
+        let prev: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Contribution(contributor.clone()))
             .unwrap_or(0);
+        let new_contribution = prev.checked_add(amount).ok_or(ContractError::Overflow)?;
+        if new_contribution > cap {
+            return Err(ContractError::AllowlistCapExceeded);
+        }
 
-        let new_contribution = prev
-            .checked_add(effective_amount)
-            .ok_or(ContractError::Overflow)?;
+        let effective_amount = accept_contribution(&env, contributor.clone(), amount, false)?;
+        mint_shares(&env, &contributor, effective_amount);
+        mint_receipt(&env, &contributor, effective_amount);
+        Ok(effective_amount)
+    }
+
+    /// Contribute and reserve a slot in a limited reward tier.
+    ///
+    /// The contributor's total after this contribution must meet the tier's
+    /// `min_amount`. Each address can hold one reserved tier; the slot is
+    /// released if the contributor later takes their whole contribution out.
+    ///
+    /// Returns the amount actually transferred, as for `contribute`.
+    ///
+    /// # Errors
+    /// * `TierNotFound`    – `tier_id` does not exist.
+    /// * `TierUnavailable` – The tier's availability window is not open.
+    /// * `TierSoldOut`     – Every slot has been claimed.
+    /// * `AlreadyClaimed`  – The contributor already reserved a tier.
+    pub fn contribute_for_tier(
+        env: Env,
+        contributor: Address,
+        amount: i128,
+        tier_id: u32,
+    ) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
+
+        let mut tiers = Self::reward_tiers(env.clone());
+        let mut tier = tiers.get(tier_id).ok_or(ContractError::TierNotFound)?;
+
+        let now = env.ledger().timestamp();
+        if tier.opens_at.is_some_and(|opens| now < opens)
+            || tier.closes_at.is_some_and(|closes| now > closes)
+        {
+            return Err(ContractError::TierUnavailable);
+        }
+        if tier.supply.is_some_and(|supply| tier.claimed >= supply) {
+            return Err(ContractError::TierSoldOut);
+        }
+
+        let selected_key = DataKey::SelectedTier(contributor.clone());
+        if env.storage().persistent().has(&selected_key) {
+            return Err(ContractError::AlreadyClaimed);
+        }
+
+        let effective_amount = accept_contribution(&env, contributor.clone(), amount, false)?;
+
+        let contribution: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Contribution(contributor.clone()))
+            .unwrap_or(0);
+        if contribution < tier.min_amount {
+            panic!("amount below tier minimum");
+        }
+
+        tier.claimed += 1;
+        tiers.set(tier_id, tier);
+        env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+        env.storage().persistent().set(&selected_key, &tier_id);
+        env.storage()
+            .persistent()
+            .extend_ttl(&selected_key, 100, 100);
+
+        env.events().publish(
+            ("campaign", "tier_reserved"),
+            (contributor.clone(), tier_id),
+        );
+
+        mint_shares(&env, &contributor, effective_amount);
+        mint_receipt(&env, &contributor, effective_amount);
+
+        Ok(effective_amount)
+    }
+
+    /// Contribute one of the campaign's secondary tokens.
+    ///
+    /// The amount is valued in goal units through the price feed and that
+    /// value counts towards the goal, minimum contribution and hard cap. The
+    /// raw amount is tracked per token so refunds and payouts return the same
+    /// asset. Contributions that would pass the hard cap are rejected with
+    /// `HardCapExceeded` regardless of the [`HardCapPolicy`].
+    ///
+    /// Returns the value credited, in goal units.
+    ///
+    /// # Errors
+    /// * `UnsupportedToken` – `token` is not an accepted secondary token.
+    /// * `InvalidPrice`     – The price feed returned a non-positive price.
+    pub fn contribute_token(
+        env: Env,
+        contributor: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let now = env.ledger().timestamp();
+        let last_time_key = DataKey::LastContributionTime(contributor.clone());
+        if let Some(last_time) = env.storage().persistent().get::<_, u64>(&last_time_key) {
+            if now < last_time + CONTRIBUTION_COOLDOWN {
+                return Err(ContractError::RateLimitExceeded);
+            }
+        }
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
+
+        contributor.require_auth();
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
+
+        if !Self::accepted_tokens(env.clone()).contains(&token) {
+            return Err(ContractError::UnsupportedToken);
+        }
+
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if now > deadline {
+            return Err(ContractError::CampaignEnded);
+        }
+
+        let feed: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PriceFeed)
+            .expect("price feed not set");
+        let price = PriceFeedClient::new(&env, &feed).price(&token);
+        if price <= 0 {
+            return Err(ContractError::InvalidPrice);
+        }
+        let value = amount.checked_mul(price).ok_or(ContractError::Overflow)? / PRICE_SCALE;
+
+        let min_contribution: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinContribution)
+            .unwrap();
+        if value < min_contribution {
+            panic!("amount below minimum");
+        }
+        check_contribution_limit(&env, &contributor, value)?;
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
+        let new_total = total.checked_add(value).ok_or(ContractError::Overflow)?;
+        if new_total > hard_cap {
+            return Err(ContractError::HardCapExceeded);
+        }
+
+        token::Client::new(&env, &token).transfer(
+            &contributor,
+            &env.current_contract_address(),
+            &amount,
+        );
 
+        // Goal-unit totals, shared with campaign-token contributions.
+        let contribution_key = DataKey::Contribution(contributor.clone());
+        let prev: i128 = env
+            .storage()
+            .persistent()
+            .get(&contribution_key)
+            .unwrap_or(0);
+        let new_contribution = prev.checked_add(value).ok_or(ContractError::Overflow)?;
+        checkpoint_weight(&env, &contributor);
         env.storage()
             .persistent()
             .set(&contribution_key, &new_contribution);
         env.storage()
             .persistent()
             .extend_ttl(&contribution_key, 100, 100);
-
-        // Update the global total raised with overflow protection.
-        let new_total = total
-            .checked_add(effective_amount)
-            .ok_or(ContractError::Overflow)?;
-
         env.storage()
             .instance()
             .set(&DataKey::TotalRaised, &new_total);
 
-        if new_total == hard_cap {
+        // Raw per-token balances, used to pay the same asset back out.
+        let balance_key = DataKey::TokenContribution(contributor.clone(), token.clone());
+        let balance =
+            add_token_balance(env.storage().persistent().get(&balance_key), amount, value)?;
+        env.storage().persistent().set(&balance_key, &balance);
+        env.storage()
+            .persistent()
+            .extend_ttl(&balance_key, 100, 100);
+
+        let raised_key = DataKey::TokenRaised(token.clone());
+        let raised = add_token_balance(env.storage().instance().get(&raised_key), amount, value)?;
+        env.storage().instance().set(&raised_key, &raised);
+
+        if new_total >= hard_cap {
             env.events()
                 .publish(("campaign", "hard_cap_reached"), hard_cap);
         }
 
-        // Track contributor address if new.
         let mut contributors: Vec<Address> = env
             .storage()
             .persistent()
@@ -340,24 +1493,155 @@ impl CrowdfundContract {
                 .extend_ttl(&DataKey::Contributors, 100, 100);
         }
 
-        // Emit contribution event
-        env.events()
-            .publish(("campaign", "contributed"), (contributor, effective_amount));
+        env.events().publish(
+            ("campaign", "token_contributed"),
+            (contributor.clone(), token, amount, value),
+        );
 
-        // Update last contribution time for rate limiting
         env.storage().persistent().set(&last_time_key, &now);
         env.storage()
             .persistent()
             .extend_ttl(&last_time_key, 100, 100);
 
-        Ok(())
+        mint_receipt(&env, &contributor, value);
+
+        Ok(value)
+    }
+
+    /// Take some or all of a contribution back out while the campaign is
+    /// Active and before the deadline.
+    ///
+    /// If the creator configured a lock period, withdrawals are blocked that
+    /// many seconds before the deadline. A configured penalty is deducted
+    /// from `amount` and paid to the creator. An address whose contribution
+    /// drops to zero is removed from the contributor list, and one that drops
+    /// below their reserved tier's minimum gives the slot back. Only the share
+    /// contributed in the campaign token can be withdrawn. In share mode the
+    /// contributor must also hold `amount` shares, which are burned. The
+    /// withdrawn amount comes off the contributor's newest receipts first.
+    ///
+    /// Returns the amount sent back to the contributor after any penalty.
+    ///
+    /// # Errors
+    /// * `CampaignEnded`            – The deadline has passed.
+    /// * `WithdrawalLocked`         – The campaign is inside its lock period.
+    /// * `InsufficientContribution` – `amount` is not positive or exceeds the contribution.
+    pub fn withdraw_contribution(
+        env: Env,
+        contributor: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        contributor.require_auth();
+
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        let now = env.ledger().timestamp();
+        if now > deadline {
+            return Err(ContractError::CampaignEnded);
+        }
+
+        let terms = Self::withdrawal_terms(env.clone());
+        if now.saturating_add(terms.lock_period) > deadline {
+            return Err(ContractError::WithdrawalLocked);
+        }
+
+        let contribution_key = DataKey::Contribution(contributor.clone());
+        let contribution: i128 = env
+            .storage()
+            .persistent()
+            .get(&contribution_key)
+            .unwrap_or(0);
+        if amount <= 0 || amount > primary_share(&env, &contributor, contribution) {
+            return Err(ContractError::InsufficientContribution);
+        }
+        if share_mode(&env) {
+            if amount > share_balance(&env, &contributor) {
+                return Err(ContractError::InsufficientContribution);
+            }
+            burn_shares(&env, &contributor, amount);
+        }
+
+        unrefer(&env, &contributor, amount);
+        unmatch(&env, &contributor, amount);
+
+        let remaining = contribution - amount;
+        checkpoint_weight(&env, &contributor);
+        if remaining == 0 {
+            env.storage().persistent().remove(&contribution_key);
+            remove_contributor(&env, &contributor);
+            release_tier(&env, &contributor);
+            void_receipts(&env, &contributor);
+        } else {
+            env.storage()
+                .persistent()
+                .set(&contribution_key, &remaining);
+            env.storage()
+                .persistent()
+                .extend_ttl(&contribution_key, 100, 100);
+            release_tier_if_short(&env, &contributor, remaining);
+            shrink_receipts(&env, &contributor, amount);
+        }
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalRaised, &(total - amount));
+
+        let penalty = amount
+            .checked_mul(terms.penalty_bps as i128)
+            .ok_or(ContractError::Overflow)?
+            / 10_000;
+        let net = amount - penalty;
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&env.current_contract_address(), &contributor, &net);
+        if penalty > 0 {
+            let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+            token_client.transfer(&env.current_contract_address(), &creator, &penalty);
+        }
+
+        env.events().publish(
+            ("campaign", "contribution_withdrawn"),
+            (contributor, amount, penalty),
+        );
+
+        Ok(net)
     }
 
     /// Pledge tokens to the campaign without transferring them immediately.
     ///
-    /// The pledger must authorize the call. Pledges are recorded off-chain
-    /// and only collected if the goal is met after the deadline.
+    /// The pledger must authorize the call and grant this contract a token
+    /// allowance covering their whole pledge. Pledges are only collected,
+    /// through that allowance, if the goal is met after the deadline.
+    /// Contributions and pledges together may not exceed the hard cap.
+    ///
+    /// # Errors
+    /// * `PledgeLocked`          – The deadline is within the pledge lock window.
+    /// * `HardCapExceeded`       – The pledge would take the total past the hard cap.
+    /// * `InsufficientAllowance` – The allowance does not cover the pledge.
     pub fn pledge(env: Env, pledger: Address, amount: i128) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        if env.storage().instance().has(&DataKey::AllowlistRoot) {
+            return Err(ContractError::AllowlistActive);
+        }
+
         pledger.require_auth();
 
         let min_contribution: i128 = env
@@ -368,44 +1652,66 @@ impl CrowdfundContract {
         if amount < min_contribution {
             panic!("amount below minimum");
         }
+        check_contribution_limit(&env, &pledger, amount)?;
 
+        let now = env.ledger().timestamp();
         let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
-        if env.ledger().timestamp() > deadline {
+        if now > deadline {
             return Err(ContractError::CampaignEnded);
         }
+        let window: u64 = env
+            .storage()
+            .instance()
+            .get(&PledgeKey::PledgeLockWindow)
+            .unwrap_or(0);
+        if window > 0 && deadline - now <= window {
+            return Err(ContractError::PledgeLocked);
+        }
 
         // Update the pledger's running total.
-        let pledge_key = DataKey::Pledge(pledger.clone());
+        let pledge_key = PledgeKey::Pledge(pledger.clone());
         let prev: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
-        env.storage()
-            .persistent()
-            .set(&pledge_key, &(prev + amount));
+
+        let new_pledge = prev.checked_add(amount).ok_or(ContractError::Overflow)?;
+        check_pledge_hard_cap(&env, amount)?;
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let allowance = token::Client::new(&env, &token_address)
+            .allowance(&pledger, &env.current_contract_address());
+        if allowance < new_pledge {
+            return Err(ContractError::InsufficientAllowance);
+        }
+
+        env.storage().persistent().set(&pledge_key, &new_pledge);
         env.storage().persistent().extend_ttl(&pledge_key, 100, 100);
 
         // Update the global total pledged.
         let total_pledged: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::TotalPledged)
+            .get(&PledgeKey::TotalPledged)
             .unwrap_or(0);
+        let new_total_pledged = total_pledged
+            .checked_add(amount)
+            .ok_or(ContractError::Overflow)?;
         env.storage()
             .instance()
-            .set(&DataKey::TotalPledged, &(total_pledged + amount));
+            .set(&PledgeKey::TotalPledged, &new_total_pledged);
 
         // Track pledger address if new.
         let mut pledgers: Vec<Address> = env
             .storage()
             .persistent()
-            .get(&DataKey::Pledgers)
+            .get(&PledgeKey::Pledgers)
             .unwrap_or_else(|| Vec::new(&env));
         if !pledgers.contains(&pledger) {
             pledgers.push_back(pledger.clone());
             env.storage()
                 .persistent()
-                .set(&DataKey::Pledgers, &pledgers);
+                .set(&PledgeKey::Pledgers, &pledgers);
             env.storage()
                 .persistent()
-                .extend_ttl(&DataKey::Pledgers, 100, 100);
+                .extend_ttl(&PledgeKey::Pledgers, 100, 100);
         }
 
         // Emit pledge event
@@ -415,32 +1721,103 @@ impl CrowdfundContract {
         Ok(())
     }
 
-    /// Collect all pledges after the deadline when the goal is met.
+    /// Revoke a pledge entirely.
     ///
-    /// This function transfers tokens from all pledgers to the contract.
-    /// Only callable after the deadline and when the combined total of
-    /// contributions and pledges meets or exceeds the goal.
-    pub fn collect_pledges(env: Env) -> Result<(), ContractError> {
-        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
-        if status != Status::Active {
-            panic!("campaign is not active");
-        }
+    /// Returns the amount that was pledged.
+    ///
+    /// # Errors
+    /// * `NoPledge`      – The address has no pending pledge.
+    /// * `CampaignEnded` – The deadline has passed.
+    /// * `PledgeLocked`  – The deadline is within the pledge lock window.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    pub fn cancel_pledge(env: Env, pledger: Address) -> Result<i128, ContractError> {
+        require_migrated(&env);
 
-        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
-        if env.ledger().timestamp() <= deadline {
-            return Err(ContractError::CampaignStillActive);
-        }
+        pledger.require_auth();
 
-        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
-        let total_raised: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
-        let total_pledged: i128 = env
+        let previous = change_pledge(&env, &pledger, 0)?;
+
+        env.events()
+            .publish(("campaign", "pledge_cancelled"), (pledger, previous));
+
+        Ok(previous)
+    }
+
+    /// Change a pending pledge to `new_amount`; zero cancels it.
+    ///
+    /// A higher amount must stay within the contribution limit and the hard
+    /// cap, and be covered by the pledger's allowance.
+    ///
+    /// # Errors
+    /// * `NoPledge`                  – The address has no pending pledge.
+    /// * `CampaignEnded`             – The deadline has passed.
+    /// * `PledgeLocked`              – The deadline is within the pledge lock window.
+    /// * `ContributionLimitExceeded` – The increase exceeds the per-address limit.
+    /// * `HardCapExceeded`           – The increase would pass the hard cap.
+    /// * `InsufficientAllowance`     – The allowance does not cover the new amount.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If `new_amount` is positive but below the minimum contribution.
+    pub fn amend_pledge(env: Env, pledger: Address, new_amount: i128) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        pledger.require_auth();
+
+        let min_contribution: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::TotalPledged)
-            .unwrap_or(0);
+            .get(&DataKey::MinContribution)
+            .unwrap();
+        if new_amount < 0 || (new_amount > 0 && new_amount < min_contribution) {
+            panic!("amount below minimum");
+        }
+
+        let previous = change_pledge(&env, &pledger, new_amount)?;
+
+        env.events().publish(
+            ("campaign", "pledge_amended"),
+            (pledger, previous, new_amount),
+        );
+
+        Ok(())
+    }
+
+    /// Collect all pledges after the deadline when the goal is met.
+    ///
+    /// This function pulls each pledge through the pledger's allowance and
+    /// records it as a contribution. A pull that fails marks that pledge
+    /// Defaulted instead of aborting the collection. Only callable after the
+    /// deadline and when the combined total of contributions and pledges
+    /// meets or exceeds the goal.
+    ///
+    /// Only collected pledges are added to the total raised, so if defaults
+    /// leave it short of the goal `withdraw` fails and the campaign can be
+    /// refunded. Pledges are collected only up to the hard cap; a pledge
+    /// that would pass it is pulled in part or not at all.
+    pub fn collect_pledges(env: Env) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() <= deadline {
+            return Err(ContractError::CampaignStillActive);
+        }
+
+        // A partial refund batch has already started paying contributors back.
+        if env.storage().instance().has(&DataKey::RefundCursor) {
+            return Err(ContractError::SettlementInProgress);
+        }
 
         // Check if combined total meets the goal
-        if total_raised + total_pledged < goal {
+        let total_raised: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if !pledges_cover_goal(&env, total_raised) {
             return Err(ContractError::GoalNotReached);
         }
 
@@ -450,34 +1827,17 @@ impl CrowdfundContract {
         let pledgers: Vec<Address> = env
             .storage()
             .persistent()
-            .get(&DataKey::Pledgers)
+            .get(&PledgeKey::Pledgers)
             .unwrap_or_else(|| Vec::new(&env));
 
         // Collect pledges from all pledgers
         for pledger in pledgers.iter() {
-            let pledge_key = DataKey::Pledge(pledger.clone());
-            let amount: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
-            if amount > 0 {
-                // Transfer tokens from pledger to contract
-                token_client.transfer(&pledger, &env.current_contract_address(), &amount);
-
-                // Clear the pledge
-                env.storage().persistent().set(&pledge_key, &0i128);
-                env.storage().persistent().extend_ttl(&pledge_key, 100, 100);
-            }
+            collect_pledge(&env, &token_client, &pledger, total_raised);
         }
 
-        // Update total raised to include collected pledges
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalRaised, &(total_raised + total_pledged));
-
-        // Reset total pledged
-        env.storage().instance().set(&DataKey::TotalPledged, &0i128);
-
-        // Emit pledges collected event
-        env.events()
-            .publish(("campaign", "pledges_collected"), total_pledged);
+        // Reset any partially-completed batch collection.
+        env.storage().instance().remove(&PledgeKey::PledgeCursor);
+        finish_pledge_collection(&env, total_raised);
 
         Ok(())
     }
@@ -486,8 +1846,14 @@ impl CrowdfundContract {
     /// deadline, and only if the goal has been met.
     ///
     /// If a platform fee is configured, deducts the fee and transfers it to
-    /// the platform address, then sends the remainder to the creator.
+    /// the platform address. Referral bonuses are then paid out of what is
+    /// left, and the remainder goes to the creator. If
+    /// roadmap milestones carry a payout share, that share is held in escrow
+    /// and only the rest is paid out here. Each secondary token is paid out
+    /// separately, with the fee taken in that token.
     pub fn withdraw(env: Env) -> Result<(), ContractError> {
+        require_migrated(&env);
+
         let paused: bool = env
             .storage()
             .instance()
@@ -510,6 +1876,11 @@ impl CrowdfundContract {
             return Err(ContractError::CampaignStillActive);
         }
 
+        // Pledges collected by a partial batch are not yet in TotalRaised.
+        if env.storage().instance().has(&PledgeKey::PledgeCursor) {
+            return Err(ContractError::SettlementInProgress);
+        }
+
         let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
         let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
         if total < goal {
@@ -519,33 +1890,71 @@ impl CrowdfundContract {
         let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
         let token_client = token::Client::new(&env, &token_address);
 
-        // Calculate and transfer platform fee if configured.
-        let platform_config: Option<PlatformConfig> =
-            env.storage().instance().get(&DataKey::PlatformConfig);
-
-        let creator_payout = if let Some(config) = platform_config {
-            // Calculate fee using checked arithmetic to prevent overflow.
-            let fee = total
-                .checked_mul(config.fee_bps as i128)
-                .expect("fee calculation overflow")
-                .checked_div(10_000)
-                .expect("fee division by zero");
-
-            // Transfer fee to platform.
-            token_client.transfer(&env.current_contract_address(), &config.address, &fee);
-
-            // Emit event with fee details.
-            env.events()
-                .publish(("campaign", "fee_transferred"), (&config.address, fee));
+        // In escrow mode, milestone tranches stay in the contract until
+        // released with `release_milestone`.
+        let escrow_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBps)
+            .unwrap_or(0);
+        let primary_total = primary_raised(&env, total);
+        let held = primary_total
+            .checked_mul(escrow_bps as i128)
+            .expect("escrow calculation overflow")
+            / 10_000;
+        let payout = primary_total - held;
+        if escrow_bps > 0 {
+            env.storage()
+                .instance()
+                .set(&DataKey::EscrowBase, &primary_total);
+            env.storage()
+                .instance()
+                .set(&DataKey::EscrowReleased, &0i128);
+        }
 
-            // Calculate creator payout.
-            total.checked_sub(fee).expect("creator payout underflow")
+        // Deduct the platform fee and transfer the remainder to the creator.
+        let creator_payout = deduct_platform_fee(&env, &token_client, payout, total);
+        let creator_payout =
+            creator_payout - pay_referral_bonuses(&env, &token_client, creator_payout);
+
+        // With vesting terms the net payout streams via `claim_vested`.
+        let vesting: Option<VestingTerms> = env.storage().instance().get(&VestingKey::VestingTerms);
+        if let Some(terms) = vesting {
+            env.storage().instance().set(
+                &VestingKey::Vesting,
+                &VestingStream {
+                    start: env.ledger().timestamp(),
+                    cliff: terms.cliff,
+                    duration: terms.duration,
+                    base: primary_total,
+                    total: creator_payout,
+                    claimed: 0,
+                    halted_at: None,
+                },
+            );
+            env.events().publish(
+                ("campaign", "vesting_started"),
+                (creator_payout, terms.cliff, terms.duration),
+            );
         } else {
-            total
-        };
+            token_client.transfer(&env.current_contract_address(), &creator, &creator_payout);
+        }
 
-        // Transfer remainder to creator.
-        token_client.transfer(&env.current_contract_address(), &creator, &creator_payout);
+        for secondary in Self::accepted_tokens(env.clone()).iter() {
+            let raised_key = DataKey::TokenRaised(secondary.clone());
+            let raised: Option<TokenBalance> = env.storage().instance().get(&raised_key);
+            let Some(raised) = raised else { continue };
+            env.storage().instance().remove(&raised_key);
+
+            let secondary_client = token::Client::new(&env, &secondary);
+            let secondary_payout =
+                deduct_platform_fee(&env, &secondary_client, raised.amount, total);
+            secondary_client.transfer(&env.current_contract_address(), &creator, &secondary_payout);
+            env.events().publish(
+                ("campaign", "withdrawn_token"),
+                (creator.clone(), secondary, raised.amount),
+            );
+        }
 
         env.storage().instance().set(&DataKey::TotalRaised, &0i128);
         env.storage()
@@ -554,14 +1963,25 @@ impl CrowdfundContract {
 
         // Emit withdrawal event
         env.events()
-            .publish(("campaign", "withdrawn"), (creator.clone(), total));
+            .publish(("campaign", "withdrawn"), (creator.clone(), payout));
 
         Ok(())
     }
 
-    /// Refund all contributors — callable by anyone after the deadline
-    /// if the goal was **not** met.
+    /// Mark the campaign as refunded — callable by anyone after the deadline
+    /// if the goal was **not** met, counting outstanding pledges.
+    ///
+    /// Pledges that cover the goal on paper must be collected first, even if
+    /// some can no longer be pulled; their defaults are what let a refund
+    /// through.
+    ///
+    /// Sponsor matching funds are returned to the sponsor. No other tokens
+    /// are moved here. Each contributor pulls their own funds back
+    /// with [`claim_refund`](Self::claim_refund), which keeps the cost of this
+    /// call independent of the number of contributors.
     pub fn refund(env: Env) -> Result<(), ContractError> {
+        require_migrated(&env);
+
         let paused: bool = env
             .storage()
             .instance()
@@ -581,35 +2001,15 @@ impl CrowdfundContract {
             return Err(ContractError::CampaignStillActive);
         }
 
-        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
-        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
-        if total >= goal {
-            return Err(ContractError::GoalReached);
+        // Pledges collected by a partial batch are not yet in TotalRaised.
+        if env.storage().instance().has(&PledgeKey::PledgeCursor) {
+            return Err(ContractError::SettlementInProgress);
         }
 
-        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
-        let token_client = token::Client::new(&env, &token_address);
-
-        let contributors: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Contributors)
-            .unwrap();
-
-        for contributor in contributors.iter() {
-            let contribution_key = DataKey::Contribution(contributor.clone());
-            let amount: i128 = env
-                .storage()
-                .persistent()
-                .get(&contribution_key)
-                .unwrap_or(0);
-            if amount > 0 {
-                token_client.transfer(&env.current_contract_address(), &contributor, &amount);
-                env.storage().persistent().set(&contribution_key, &0i128);
-                env.storage()
-                    .persistent()
-                    .extend_ttl(&contribution_key, 100, 100);
-            }
+        // Outstanding pledges that cover the goal must be collected first.
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if pledges_cover_goal(&env, total) {
+            return Err(ContractError::GoalReached);
         }
 
         env.storage().instance().set(&DataKey::TotalRaised, &0i128);
@@ -617,12 +2017,23 @@ impl CrowdfundContract {
             .instance()
             .set(&DataKey::Status, &Status::Refunded);
 
+        return_matched_funds(&env);
+        return_grants(&env);
+
+        env.events().publish(("campaign", "refunded"), total);
+
         Ok(())
     }
 
-    /// Cancel the campaign and refund all contributors — callable only by
-    /// the creator while the campaign is still Active.
+    /// Cancel the campaign — callable only by the creator while the campaign
+    /// is still Active.
+    ///
+    /// Contributors reclaim their funds with
+    /// [`claim_refund`](Self::claim_refund); sponsor matching funds are
+    /// returned to the sponsor immediately.
     pub fn cancel(env: Env) {
+        require_migrated(&env);
+
         let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
         if status != Status::Active {
             panic!("campaign is not active");
@@ -631,509 +2042,4805 @@ impl CrowdfundContract {
         let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
         creator.require_auth();
 
-        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
-        let token_client = token::Client::new(&env, &token_address);
-
-        let contributors: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Contributors)
-            .unwrap();
-
-        for contributor in contributors.iter() {
-            let contribution_key = DataKey::Contribution(contributor.clone());
-            let amount: i128 = env
-                .storage()
-                .persistent()
-                .get(&contribution_key)
-                .unwrap_or(0);
-            if amount > 0 {
-                token_client.transfer(&env.current_contract_address(), &contributor, &amount);
-                env.storage().persistent().set(&contribution_key, &0i128);
-                env.storage()
-                    .persistent()
-                    .extend_ttl(&contribution_key, 100, 100);
-            }
-        }
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
 
         env.storage().instance().set(&DataKey::TotalRaised, &0i128);
         env.storage()
             .instance()
             .set(&DataKey::Status, &Status::Cancelled);
-    }
 
-    /// Upgrade the contract to a new WASM implementation — admin-only.
-    ///
-    /// This function allows the designated admin to upgrade the contract's WASM code
-    /// without changing the contract's address or storage. The new WASM hash must be
-    /// provided and the caller must be authorized as the admin.
-    ///
-    /// # Arguments
-    /// * `new_wasm_hash` – The SHA-256 hash of the new WASM binary to deploy.
-    ///
-    /// # Panics
-    /// * If the caller is not the admin.
-    pub fn upgrade(env: Env, new_wasm_hash: soroban_sdk::BytesN<32>) {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+        return_matched_funds(&env);
+        return_grants(&env);
 
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        env.events().publish(("campaign", "cancelled"), total);
     }
 
-    /// Pause or unpause the contract — creator-only.
+    /// Return a contributor's funds after the campaign was refunded or
+    /// cancelled.
     ///
-    /// When paused, all contributions, withdrawals, and refunds are blocked.
-    /// This is a security mechanism to halt operations in case of detected
-    /// vulnerabilities or external threats.
+    /// Anyone may call this on behalf of a contributor; the tokens are always
+    /// sent to `contributor`. Each contributor can claim exactly once. In
+    /// share mode the refund follows the share balance, which is burned.
     ///
-    /// # Arguments
-    /// * `paused` – True to pause, false to unpause.
-    pub fn set_paused(env: Env, paused: bool) {
-        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        creator.require_auth();
+    /// # Errors
+    /// * `NotRefundable`  – The campaign is not Refunded or Cancelled.
+    /// * `AlreadyClaimed` – The contributor's refund was already paid out.
+    /// * `NothingToClaim` – The address has no contribution on record.
+    pub fn claim_refund(env: Env, contributor: Address) -> Result<i128, ContractError> {
+        require_migrated(&env);
 
-        env.storage().instance().set(&DataKey::Paused, &paused);
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
 
-        let event_name = if paused { "paused" } else { "unpaused" };
-        env.events().publish(("campaign", event_name), ());
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Refunded && status != Status::Cancelled {
+            return Err(ContractError::NotRefundable);
+        }
+
+        if !share_mode(&env)
+            && env
+                .storage()
+                .persistent()
+                .has(&DataKey::RefundClaimed(contributor.clone()))
+        {
+            return Err(ContractError::AlreadyClaimed);
+        }
+
+        let amount = refund_entitlement(&env, &contributor);
+        if amount <= 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        pay_refund(&env, &token_client, &contributor, amount);
+
+        Ok(amount)
     }
 
-    /// Update campaign metadata — only callable by the creator while the
-    /// campaign is still Active.
+    /// Refund one page of contributors — callable by anyone after the deadline
+    /// if the goal was **not** met, counting outstanding pledges.
     ///
-    /// # Arguments
-    /// * `creator`     – The campaign creator's address (for authentication).
-    /// * `title`       – Optional new title (None to keep existing).
-    /// * `description` – Optional new description (None to keep existing).
-    /// * `socials`    – Optional new social links (None to keep existing).
-    pub fn update_metadata(
-        env: Env,
-        creator: Address,
-        title: Option<String>,
-        description: Option<String>,
-        socials: Option<String>,
-    ) {
-        // Check campaign is active.
+    /// Processes `DataKey::Contributors[start..start + limit]`, paying out every
+    /// contributor in the slice that has not already claimed. `start` must equal
+    /// the stored settlement cursor so pages are processed in order. Once the
+    /// last page is done the campaign is marked `Refunded`.
+    ///
+    /// Returns the cursor for the next page.
+    pub fn refund_batch(env: Env, start: u32, limit: u32) -> Result<u32, ContractError> {
+        require_migrated(&env);
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
+
         let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
         if status != Status::Active {
             panic!("campaign is not active");
         }
 
-        // Require creator authentication and verify caller is the creator.
-        let stored_creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        if creator != stored_creator {
-            panic!("not authorized");
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() <= deadline {
+            return Err(ContractError::CampaignStillActive);
         }
-        creator.require_auth();
 
-        // Track which fields were updated for the event.
-        let mut updated_fields: Vec<Symbol> = Vec::new(&env);
+        // Pledges collected by a partial batch are not yet in TotalRaised.
+        if env.storage().instance().has(&PledgeKey::PledgeCursor) {
+            return Err(ContractError::SettlementInProgress);
+        }
 
-        // Update title if provided.
-        if let Some(new_title) = title {
-            env.storage().instance().set(&DataKey::Title, &new_title);
-            updated_fields.push_back(Symbol::new(&env, "title"));
+        // Outstanding pledges that cover the goal must be collected first.
+        // Pledges cannot change after the deadline, so once pages are under
+        // way the check has already passed.
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if !env.storage().instance().has(&DataKey::RefundCursor) && pledges_cover_goal(&env, total)
+        {
+            return Err(ContractError::GoalReached);
         }
 
-        // Update description if provided.
-        if let Some(new_description) = description {
-            env.storage()
-                .instance()
-                .set(&DataKey::Description, &new_description);
-            updated_fields.push_back(Symbol::new(&env, "description"));
+        let cursor: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::RefundCursor)
+            .unwrap_or(0);
+        if start != cursor {
+            return Err(ContractError::InvalidCursor);
         }
 
-        // Update social links if provided.
-        if let Some(new_socials) = socials {
+        let contributors: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Contributors)
+            .unwrap();
+        let end = start.saturating_add(limit).min(contributors.len());
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+
+        for i in start..end {
+            let contributor = contributors.get(i).unwrap();
+            let amount = refund_entitlement(&env, &contributor);
+            if amount > 0 {
+                pay_refund(&env, &token_client, &contributor, amount);
+            }
+        }
+
+        if end == contributors.len() {
+            env.storage().instance().remove(&DataKey::RefundCursor);
+            env.storage().instance().set(&DataKey::TotalRaised, &0i128);
             env.storage()
                 .instance()
-                .set(&DataKey::SocialLinks, &new_socials);
-            updated_fields.push_back(Symbol::new(&env, "socials"));
+                .set(&DataKey::Status, &Status::Refunded);
+            return_matched_funds(&env);
+            return_grants(&env);
+            env.events().publish(("campaign", "refunded"), total);
+        } else {
+            env.storage().instance().set(&DataKey::RefundCursor, &end);
         }
 
-        // Emit metadata_updated event with the list of updated field names.
-        env.events().publish(
-            (
-                Symbol::new(&env, "campaign"),
-                Symbol::new(&env, "metadata_updated"),
-            ),
-            updated_fields,
-        );
+        Ok(end)
     }
 
-    /// Update the campaign deadline — only callable by the creator while the
-    /// campaign is still Active.
+    /// Collect one page of pledges after the deadline when the goal is met.
     ///
-    /// # Arguments
-    /// * `new_deadline` – The new deadline as a ledger timestamp (must be greater than current deadline).
+    /// Processes `PledgeKey::Pledgers[start..start + limit]` as
+    /// [`collect_pledges`](Self::collect_pledges) does. `start` must equal
+    /// the stored settlement cursor. Collected pledges are folded into
+    /// `TotalRaised` only once the last page is done; until then `withdraw`
+    /// and `refund` are blocked. Fails while a `refund_batch` is under way.
     ///
-    /// # Panics
-    /// * If the campaign is not Active.
-    /// * If new_deadline is less than or equal to the current deadline.
-    pub fn update_deadline(env: Env, new_deadline: u64) {
-        // Check campaign is active.
+    /// Returns the cursor for the next page.
+    pub fn collect_pledges_batch(env: Env, start: u32, limit: u32) -> Result<u32, ContractError> {
+        require_migrated(&env);
+
         let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
         if status != Status::Active {
             panic!("campaign is not active");
         }
 
-        // Require creator authentication.
-        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        creator.require_auth();
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() <= deadline {
+            return Err(ContractError::CampaignStillActive);
+        }
 
-        // Get the current deadline.
-        let current_deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        // A partial refund batch has already started paying contributors back.
+        if env.storage().instance().has(&DataKey::RefundCursor) {
+            return Err(ContractError::SettlementInProgress);
+        }
 
-        // Ensure new_deadline is greater than current_deadline (only extensions allowed).
-        if new_deadline <= current_deadline {
-            panic!("new deadline must be after current deadline");
+        let total_raised: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if !pledges_cover_goal(&env, total_raised) {
+            return Err(ContractError::GoalNotReached);
         }
 
-        // Update the deadline.
-        env.storage()
+        let cursor: u32 = env
+            .storage()
             .instance()
-            .set(&DataKey::Deadline, &new_deadline);
-
-        // Emit deadline_updated event with old and new deadline values.
-        env.events().publish(
-            ("campaign", "deadline_updated"),
-            (current_deadline, new_deadline),
-        );
-    }
+            .get(&PledgeKey::PledgeCursor)
+            .unwrap_or(0);
+        if start != cursor {
+            return Err(ContractError::InvalidCursor);
+        }
 
-    // ── View helpers ────────────────────────────────────────────────────
+        let pledgers: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::Pledgers)
+            .unwrap_or_else(|| Vec::new(&env));
+        let end = start.saturating_add(limit).min(pledgers.len());
 
-    /// Add a roadmap item to the campaign timeline.
-    ///
-    /// Only the creator can add roadmap items. The date must be in the future
-    /// and the description must not be empty.
-    pub fn add_roadmap_item(env: Env, date: u64, description: String) {
-        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        creator.require_auth();
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
 
-        let current_timestamp = env.ledger().timestamp();
-        if date <= current_timestamp {
-            panic!("date must be in the future");
+        for i in start..end {
+            collect_pledge(&env, &token_client, &pledgers.get(i).unwrap(), total_raised);
         }
 
-        if description.is_empty() {
-            panic!("description cannot be empty");
+        if end == pledgers.len() {
+            env.storage().instance().remove(&PledgeKey::PledgeCursor);
+            finish_pledge_collection(&env, total_raised);
+        } else {
+            env.storage().instance().set(&PledgeKey::PledgeCursor, &end);
         }
 
-        let mut roadmap: Vec<RoadmapItem> = env
+        Ok(end)
+    }
+
+    /// Announce an upgrade to a new WASM implementation — admin-only.
+    ///
+    /// The upgrade can only be executed once `eta` has passed, and `eta` must
+    /// be at least `UPGRADE_DELAY` seconds away. While the upgrade is pending,
+    /// contributors to an Active campaign may pull their funds out with
+    /// `exit_before_upgrade`. Scheduling again replaces any pending upgrade.
+    ///
+    /// # Arguments
+    /// * `new_wasm_hash` – The SHA-256 hash of the new WASM binary to deploy.
+    /// * `eta`           – Earliest ledger timestamp for `execute_upgrade`.
+    ///
+    /// # Panics
+    /// * If the admin role has been renounced.
+    /// * If `eta` is less than `UPGRADE_DELAY` seconds from now.
+    pub fn schedule_upgrade(env: Env, new_wasm_hash: soroban_sdk::BytesN<32>, eta: u64) {
+        let admin: Address = env
             .storage()
             .instance()
-            .get(&DataKey::Roadmap)
-            .unwrap_or_else(|| Vec::new(&env));
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
 
-        let item = RoadmapItem {
-            date,
-            description: description.clone(),
-        };
+        if eta < env.ledger().timestamp() + UPGRADE_DELAY {
+            panic!("eta is before the minimum upgrade delay");
+        }
 
-        roadmap.push_back(item.clone());
-        env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+        let pending = PendingUpgrade {
+            wasm_hash: new_wasm_hash.clone(),
+            eta,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingUpgrade, &pending);
 
         env.events()
-            .publish(("campaign", "roadmap_item_added"), (date, description));
+            .publish(("campaign", "upgrade_scheduled"), (new_wasm_hash, eta));
     }
 
-    /// Returns the full ordered list of roadmap items.
-    pub fn roadmap(env: Env) -> Vec<RoadmapItem> {
-        env.storage()
+    /// Withdraw a pending upgrade announcement — admin-only.
+    ///
+    /// # Panics
+    /// * If there is no pending upgrade.
+    pub fn cancel_upgrade(env: Env) {
+        let admin: Address = env
+            .storage()
             .instance()
-            .get(&DataKey::Roadmap)
-            .unwrap_or_else(|| Vec::new(&env))
-    }
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
 
-    /// Add a stretch goal milestone to the campaign.
-    ///
-    /// Only the creator can add stretch goals. The milestone must be greater
-    /// than the primary goal.
-    pub fn add_stretch_goal(env: Env, milestone: i128) {
-        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        creator.require_auth();
+        let pending: PendingUpgrade = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingUpgrade)
+            .expect("no pending upgrade");
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
 
-        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
-        if milestone <= goal {
-            panic!("stretch goal must be greater than primary goal");
-        }
+        env.events()
+            .publish(("campaign", "upgrade_cancelled"), pending.wasm_hash);
+    }
 
-        let mut stretch_goals: Vec<i128> = env
+    /// Execute the pending upgrade once its notice period has elapsed —
+    /// admin-only.
+    ///
+    /// The contract's address and storage are preserved.
+    ///
+    /// # Panics
+    /// * If there is no pending upgrade.
+    /// * If the upgrade's `eta` has not been reached.
+    pub fn execute_upgrade(env: Env) {
+        let admin: Address = env
             .storage()
             .instance()
-            .get(&DataKey::StretchGoals)
-            .unwrap_or_else(|| Vec::new(&env));
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
 
-        stretch_goals.push_back(milestone);
-        env.storage()
+        let pending: PendingUpgrade = env
+            .storage()
             .instance()
-            .set(&DataKey::StretchGoals, &stretch_goals);
+            .get(&DataKey::PendingUpgrade)
+            .expect("no pending upgrade");
+        if env.ledger().timestamp() < pending.eta {
+            panic!("upgrade notice period has not elapsed");
+        }
+
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
+
+        env.events()
+            .publish(("campaign", "upgrade_executed"), pending.wasm_hash.clone());
+
+        env.deployer()
+            .update_current_contract_wasm(pending.wasm_hash);
     }
 
-    /// Add a reward tier (creator only). Rejects min_amount <= 0.
-    pub fn add_reward_tier(env: Env, creator: Address, name: String, min_amount: i128) {
+    /// Pull a contribution back out while an upgrade is pending.
+    ///
+    /// Only available while the campaign is Active, before its deadline and
+    /// before the pending upgrade's `eta`, so backers who disagree with an
+    /// announced change can leave before it takes effect. The full
+    /// contribution is returned.
+    ///
+    /// # Errors
+    /// * `ContractPaused`   – The contract is paused.
+    /// * `NoPendingUpgrade` – No upgrade is pending, or its `eta` has passed.
+    /// * `CampaignEnded`    – The deadline has passed.
+    /// * `SettlementInProgress` – A `refund_batch` is under way.
+    /// * `NothingToClaim`   – The contributor has no contribution on record.
+    /// * `InsufficientContribution` – In share mode, the contributor no longer
+    ///   holds shares covering the contribution.
+    pub fn exit_before_upgrade(env: Env, contributor: Address) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        if paused {
+            return Err(ContractError::ContractPaused);
+        }
+
         let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
         if status != Status::Active {
             panic!("campaign is not active");
         }
 
-        let stored_creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
-        if creator != stored_creator {
-            panic!("not authorized");
+        contributor.require_auth();
+
+        let pending: Option<PendingUpgrade> =
+            env.storage().instance().get(&DataKey::PendingUpgrade);
+        match pending {
+            Some(ref p) if env.ledger().timestamp() < p.eta => {}
+            _ => return Err(ContractError::NoPendingUpgrade),
         }
-        creator.require_auth();
 
-        if min_amount <= 0 {
-            panic!("min_amount must be greater than 0");
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() > deadline {
+            return Err(ContractError::CampaignEnded);
+        }
+        if env.storage().instance().has(&DataKey::RefundCursor) {
+            return Err(ContractError::SettlementInProgress);
         }
 
-        let mut tiers: Vec<RewardTier> = env
+        let contribution_key = DataKey::Contribution(contributor.clone());
+        let amount: i128 = env
             .storage()
+            .persistent()
+            .get(&contribution_key)
+            .unwrap_or(0);
+        if amount <= 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+        if share_mode(&env) {
+            if share_balance(&env, &contributor) < amount {
+                return Err(ContractError::InsufficientContribution);
+            }
+            burn_shares(&env, &contributor, amount);
+        }
+
+        // Only the campaign-token part was ever matched.
+        let primary = primary_share(&env, &contributor, amount);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        env.storage()
             .instance()
-            .get(&DataKey::RewardTiers)
-            .unwrap_or_else(|| Vec::new(&env));
+            .set(&DataKey::TotalRaised, &(total - amount));
+        checkpoint_weight(&env, &contributor);
+        env.storage().persistent().remove(&contribution_key);
+        remove_contributor(&env, &contributor);
+        release_tier(&env, &contributor);
+        void_receipts(&env, &contributor);
+        unrefer(&env, &contributor, primary);
+        unmatch(&env, &contributor, primary);
 
-        tiers.push_back(RewardTier {
-            name: name.clone(),
-            min_amount,
-        });
-        env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        return_contribution(&env, &token_client, &contributor, amount);
 
         env.events()
-            .publish(("campaign", "reward_tier_added"), (name, min_amount));
+            .publish(("campaign", "contribution_exited"), (contributor, amount));
+
+        Ok(amount)
     }
 
-    /// Returns the full ordered list of reward tiers.
-    pub fn reward_tiers(env: Env) -> Vec<RewardTier> {
+    /// Bring stored data up to the layout expected by this code version.
+    ///
+    /// After an upgrade that bumps `CONTRACT_VERSION`, business entrypoints
+    /// fail with `MigrationPending` until this is called. Steps run in order,
+    /// one per version, and each runs exactly once. Callable by anyone, since
+    /// the steps are fixed in code.
+    ///
+    /// Returns the new storage version.
+    pub fn migrate(env: Env) -> Result<u32, ContractError> {
+        let mut version = stored_version(&env);
+        if version >= CONTRACT_VERSION {
+            return Err(ContractError::NoMigrationPending);
+        }
+
+        let from = version;
+        while version < CONTRACT_VERSION {
+            run_migration_step(&env, version);
+            version += 1;
+        }
         env.storage()
             .instance()
-            .get(&DataKey::RewardTiers)
-            .unwrap_or_else(|| Vec::new(&env))
-    }
+            .set(&DataKey::StorageVersion, &version);
 
-    /// Returns the highest tier name the user's contribution qualifies for,
-    /// or None if the user has not contributed or no tiers are defined.
-    /// Tiers are evaluated by min_amount descending (highest qualifying tier wins).
-    pub fn get_user_tier(env: Env, user: Address) -> Option<String> {
-        let contribution: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Contribution(user))
-            .unwrap_or(0);
+        env.events()
+            .publish(("campaign", "migrated"), (from, version));
 
-        if contribution <= 0 {
-            return None;
-        }
+        Ok(version)
+    }
 
-        let tiers: Vec<RewardTier> = env
+    /// Propose a new admin — admin-only.
+    ///
+    /// The handover only takes effect once `new_admin` calls `accept_admin`,
+    /// so a mistyped address can never lock out upgrades. Proposing again
+    /// replaces any pending proposal.
+    ///
+    /// # Panics
+    /// * If the admin role has been renounced.
+    pub fn propose_admin(env: Env, new_admin: Address) {
+        let admin: Address = env
             .storage()
             .instance()
-            .get(&DataKey::RewardTiers)
-            .unwrap_or_else(|| Vec::new(&env));
-
-        if tiers.is_empty() {
-            return None;
-        }
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
 
-        let mut best: Option<RewardTier> = None;
-        for tier in tiers.iter() {
-            if contribution >= tier.min_amount {
-                let is_better = match &best {
-                    None => true,
-                    Some(ref b) => tier.min_amount > b.min_amount,
-                };
-                if is_better {
-                    best = Some(tier.clone());
-                }
-            }
-        }
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
 
-        best.map(|t| t.name)
+        env.events()
+            .publish(("campaign", "admin_proposed"), (admin, new_admin));
     }
 
-    /// Returns the next unmet stretch goal milestone.
+    /// Accept a pending admin proposal — callable only by the proposed admin.
     ///
-    /// Returns 0 if there are no stretch goals or all have been met.
-    pub fn current_milestone(env: Env) -> i128 {
-        let total_raised: i128 = env
+    /// # Panics
+    /// * If there is no pending proposal.
+    pub fn accept_admin(env: Env) {
+        let pending: Address = env
             .storage()
             .instance()
-            .get(&DataKey::TotalRaised)
-            .unwrap_or(0);
+            .get(&DataKey::PendingAdmin)
+            .expect("no pending admin");
+        pending.require_auth();
 
-        let stretch_goals: Vec<i128> = env
-            .storage()
-            .instance()
-            .get(&DataKey::StretchGoals)
-            .unwrap_or_else(|| Vec::new(&env));
+        let previous: Option<Address> = env.storage().instance().get(&DataKey::Admin);
 
-        for milestone in stretch_goals.iter() {
-            if total_raised < milestone {
-                return milestone;
-            }
-        }
+        env.storage().instance().set(&DataKey::Admin, &pending);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
 
-        0
+        env.events()
+            .publish(("campaign", "admin_transferred"), (previous, pending));
     }
-    pub fn total_raised(env: Env) -> i128 {
-        env.storage()
+
+    /// Permanently give up the admin role, making the contract immutable —
+    /// admin-only.
+    ///
+    /// Any pending proposal is discarded. This cannot be undone.
+    pub fn renounce_admin(env: Env) {
+        let admin: Address = env
+            .storage()
             .instance()
-            .get(&DataKey::TotalRaised)
-            .unwrap_or(0)
-    }
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
 
-    /// Returns the funding goal.
-    pub fn goal(env: Env) -> i128 {
-        env.storage().instance().get(&DataKey::Goal).unwrap()
-    }
+        env.storage().instance().remove(&DataKey::Admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
 
-    /// Returns the hard cap (maximum total that can be raised).
-    pub fn hard_cap(env: Env) -> i128 {
-        env.storage().instance().get(&DataKey::HardCap).unwrap()
+        env.events().publish(("campaign", "admin_renounced"), admin);
     }
 
-    /// Returns the campaign deadline.
-    pub fn deadline(env: Env) -> u64 {
-        env.storage().instance().get(&DataKey::Deadline).unwrap()
-    }
+    /// Grant `role` to `account` — requires the `Owner` role.
+    ///
+    /// Granting a role the account already holds is a no-op.
+    pub fn grant_role(env: Env, caller: Address, role: Role, account: Address) {
+        require_role(&env, &caller, Role::Owner);
 
-    /// Returns the contribution of a specific address.
-    pub fn contribution(env: Env, contributor: Address) -> i128 {
-        let contribution_key = DataKey::Contribution(contributor);
-        env.storage()
-            .persistent()
-            .get(&contribution_key)
-            .unwrap_or(0)
-    }
+        let key = DataKey::Role(role, account.clone());
+        if env.storage().instance().has(&key) {
+            return;
+        }
+        env.storage().instance().set(&key, &true);
 
-    /// Returns the pledge of a specific address.
-    pub fn pledge_amount(env: Env, pledger: Address) -> i128 {
-        let pledge_key = DataKey::Pledge(pledger);
-        env.storage().persistent().get(&pledge_key).unwrap_or(0)
-    }
+        if role == Role::Owner {
+            let owners: u32 = env
+                .storage()
+                .instance()
+                .get(&DataKey::OwnerCount)
+                .unwrap_or(0);
+            env.storage()
+                .instance()
+                .set(&DataKey::OwnerCount, &(owners + 1));
+        }
 
-    /// Returns the total amount pledged (not yet transferred).
-    pub fn total_pledged(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::TotalPledged)
-            .unwrap_or(0)
+        env.events()
+            .publish(("campaign", "role_granted"), (role, account, caller));
     }
 
-    /// Returns the minimum contribution amount.
-    pub fn min_contribution(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::MinContribution)
-            .unwrap()
-    }
+    /// Revoke `role` from `account` — requires the `Owner` role.
+    ///
+    /// # Panics
+    /// * If `account` does not hold `role`.
+    /// * If this would remove the last `Owner`.
+    pub fn revoke_role(env: Env, caller: Address, role: Role, account: Address) {
+        require_role(&env, &caller, Role::Owner);
+
+        let key = DataKey::Role(role, account.clone());
+        if !env.storage().instance().has(&key) {
+            panic!("account does not hold role");
+        }
 
-    /// Returns the primary campaign category.
-    pub fn category(env: Env) -> soroban_sdk::String {
-        env.storage().instance().get(&DataKey::Category).unwrap()
+        if role == Role::Owner {
+            let owners: u32 = env
+                .storage()
+                .instance()
+                .get(&DataKey::OwnerCount)
+                .unwrap_or(0);
+            if owners <= 1 {
+                panic!("cannot revoke last owner");
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::OwnerCount, &(owners - 1));
+        }
+        env.storage().instance().remove(&key);
+
+        env.events()
+            .publish(("campaign", "role_revoked"), (role, account, caller));
     }
 
-    /// Returns the optional descriptive tags.
-    pub fn tags(env: Env) -> Vec<soroban_sdk::String> {
-        env.storage()
+    /// Pause or unpause the contract — requires the `Pauser` role.
+    ///
+    /// When paused, all contributions, withdrawals, and refunds are blocked.
+    /// This is a security mechanism to halt operations in case of detected
+    /// vulnerabilities or external threats.
+    ///
+    /// # Arguments
+    /// * `caller` – An address holding the `Pauser` role.
+    /// * `paused` – True to pause, false to unpause.
+    pub fn set_paused(env: Env, caller: Address, paused: bool) {
+        require_role(&env, &caller, Role::Pauser);
+
+        env.storage().instance().set(&DataKey::Paused, &paused);
+
+        let event_name = if paused { "paused" } else { "unpaused" };
+        env.events().publish(("campaign", event_name), ());
+    }
+
+    /// Update campaign metadata — requires the `Editor` role while the
+    /// campaign is still Active.
+    ///
+    /// # Arguments
+    /// * `caller`      – An address holding the `Editor` role.
+    /// * `title`       – Optional new title (None to keep existing).
+    /// * `description` – Optional new description (None to keep existing).
+    /// * `socials`    – Optional new social links (None to keep existing).
+    pub fn update_metadata(
+        env: Env,
+        caller: Address,
+        title: Option<String>,
+        description: Option<String>,
+        socials: Option<String>,
+    ) {
+        require_migrated(&env);
+
+        // Check campaign is active.
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Editor);
+
+        // Track which fields were updated for the event.
+        let mut updated_fields: Vec<Symbol> = Vec::new(&env);
+
+        // Update title if provided.
+        if let Some(new_title) = title {
+            env.storage().instance().set(&DataKey::Title, &new_title);
+            updated_fields.push_back(Symbol::new(&env, "title"));
+        }
+
+        // Update description if provided.
+        if let Some(new_description) = description {
+            env.storage()
+                .instance()
+                .set(&DataKey::Description, &new_description);
+            updated_fields.push_back(Symbol::new(&env, "description"));
+        }
+
+        // Update social links if provided.
+        if let Some(new_socials) = socials {
+            env.storage()
+                .instance()
+                .set(&DataKey::SocialLinks, &new_socials);
+            updated_fields.push_back(Symbol::new(&env, "socials"));
+        }
+
+        // Emit metadata_updated event with the list of updated field names.
+        env.events().publish(
+            (
+                Symbol::new(&env, "campaign"),
+                Symbol::new(&env, "metadata_updated"),
+            ),
+            updated_fields,
+        );
+    }
+
+    /// Update the campaign deadline — requires the `Owner` role while the
+    /// campaign is still Active.
+    ///
+    /// # Arguments
+    /// * `caller`       – An address holding the `Owner` role.
+    /// * `new_deadline` – The new deadline as a ledger timestamp (must be greater than current deadline).
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If new_deadline is less than or equal to the current deadline.
+    pub fn update_deadline(env: Env, caller: Address, new_deadline: u64) {
+        require_migrated(&env);
+
+        // Check campaign is active.
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Owner);
+
+        // Get the current deadline.
+        let current_deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+
+        // Ensure new_deadline is greater than current_deadline (only extensions allowed).
+        if new_deadline <= current_deadline {
+            panic!("new deadline must be after current deadline");
+        }
+
+        // Update the deadline.
+        env.storage()
+            .instance()
+            .set(&DataKey::Deadline, &new_deadline);
+
+        // Emit deadline_updated event with old and new deadline values.
+        env.events().publish(
+            ("campaign", "deadline_updated"),
+            (current_deadline, new_deadline),
+        );
+    }
+
+    /// Choose how contributions that overshoot the hard cap are handled —
+    /// requires the `Treasurer` role, before the first contribution.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If the campaign has already received contributions.
+    pub fn set_hard_cap_policy(env: Env, caller: Address, policy: HardCapPolicy) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("policy cannot change after contributions");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::HardCapPolicy, &policy);
+
+        env.events()
+            .publish(("campaign", "hard_cap_policy_updated"), policy);
+    }
+
+    // ── View helpers ────────────────────────────────────────────────────
+
+    /// Add a roadmap item to the campaign timeline.
+    ///
+    /// Requires the `Editor` role. The date must be in the future and the
+    /// description must not be empty.
+    pub fn add_roadmap_item(env: Env, caller: Address, date: u64, description: String) {
+        require_migrated(&env);
+
+        require_role(&env, &caller, Role::Editor);
+
+        let current_timestamp = env.ledger().timestamp();
+        if date <= current_timestamp {
+            panic!("date must be in the future");
+        }
+
+        if description.is_empty() {
+            panic!("description cannot be empty");
+        }
+
+        let mut roadmap: Vec<RoadmapItem> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Roadmap)
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let item = RoadmapItem {
+            date,
+            description: description.clone(),
+            payout_bps: 0,
+            approved: false,
+            released: false,
+        };
+
+        roadmap.push_back(item.clone());
+        env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+
+        env.events()
+            .publish(("campaign", "roadmap_item_added"), (date, description));
+    }
+
+    /// Returns the full ordered list of roadmap items.
+    pub fn roadmap(env: Env) -> Vec<RoadmapItem> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Roadmap)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Switch the campaign into share mode — requires the `Treasurer` role,
+    /// before the first contribution or pledge.
+    ///
+    /// In share mode every contribution mints the same amount of a SEP-41
+    /// share token served by this contract. Refunds and escrow refunds are
+    /// paid against the share balance rather than the recorded contribution,
+    /// so backers can exit by selling shares before the deadline.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active or already has contributions or pledges.
+    /// * If the campaign accepts secondary tokens.
+    pub fn enable_share_token(env: Env, caller: Address, name: String, symbol: String) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let pledged: i128 = env
+            .storage()
+            .instance()
+            .get(&PledgeKey::TotalPledged)
+            .unwrap_or(0);
+        if total > 0 || pledged > 0 {
+            panic!("share mode cannot change after contributions");
+        }
+        if !Self::accepted_tokens(env.clone()).is_empty() {
+            panic!("share mode cannot be combined with secondary tokens");
+        }
+
+        let info = ShareTokenInfo { name, symbol };
+        env.storage().instance().set(&ShareKey::Metadata, &info);
+
+        env.events()
+            .publish(("campaign", "share_token_enabled"), info);
+    }
+
+    /// Deposit a matching pool — callable by any sponsor while the campaign
+    /// is Active and before the deadline, once per campaign.
+    ///
+    /// Every later campaign-token contribution is matched at `ratio_bps`
+    /// until the pool runs out, with at most `per_backer_cap` matched per
+    /// backer (0 for no limit). Matches count toward the goal. If the
+    /// campaign fails, the matched amount goes back to the sponsor; the
+    /// unmatched balance is reclaimable with `reclaim_matching_pool`.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active, or a pool was already funded.
+    /// * If `amount` or `ratio_bps` is zero, or `per_backer_cap` is negative.
+    pub fn fund_matching_pool(
+        env: Env,
+        sponsor: Address,
+        amount: i128,
+        ratio_bps: u32,
+        per_backer_cap: i128,
+    ) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        sponsor.require_auth();
+
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() > deadline {
+            return Err(ContractError::CampaignEnded);
+        }
+
+        if env.storage().instance().has(&MatchingKey::MatchingPool) {
+            panic!("matching pool already funded");
+        }
+        if amount <= 0 || ratio_bps == 0 || per_backer_cap < 0 {
+            panic!("invalid matching terms");
+        }
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &sponsor,
+            &env.current_contract_address(),
+            &amount,
+        );
+
+        env.storage().instance().set(
+            &MatchingKey::MatchingPool,
+            &MatchingPool {
+                sponsor: sponsor.clone(),
+                ratio_bps,
+                per_backer_cap,
+                deposited: amount,
+                matched: 0,
+                returned: false,
+                reclaimed: false,
+            },
+        );
+
+        env.events().publish(
+            ("campaign", "matching_funded"),
+            (sponsor, amount, ratio_bps, per_backer_cap),
+        );
+
+        Ok(())
+    }
+
+    /// Return the unmatched part of the pool to the sponsor once the
+    /// campaign has settled.
+    ///
+    /// Returns the amount reclaimed.
+    ///
+    /// # Errors
+    /// * `CampaignStillActive` – The campaign has not settled yet.
+    /// * `NothingToClaim`      – There is no pool or it was already reclaimed.
+    pub fn reclaim_matching_pool(env: Env) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status == Status::Active {
+            return Err(ContractError::CampaignStillActive);
+        }
+
+        let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+        let mut pool = match pool {
+            Some(pool) if !pool.reclaimed => pool,
+            _ => return Err(ContractError::NothingToClaim),
+        };
+
+        pool.sponsor.require_auth();
+
+        let unused = pool.deposited - pool.matched;
+        pool.reclaimed = true;
+        env.storage()
+            .instance()
+            .set(&MatchingKey::MatchingPool, &pool);
+
+        if unused > 0 {
+            let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+            token::Client::new(&env, &token_address).transfer(
+                &env.current_contract_address(),
+                &pool.sponsor,
+                &unused,
+            );
+        }
+
+        env.events()
+            .publish(("campaign", "matching_reclaimed"), (pool.sponsor, unused));
+
+        Ok(unused)
+    }
+
+    /// Allow `source` to send grants through `add_grant` — requires the
+    /// `Treasurer` role.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    pub fn add_grant_source(env: Env, caller: Address, source: Address) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let mut sources = Self::grant_sources(env.clone());
+        if !sources.contains(&source) {
+            sources.push_back(source.clone());
+            env.storage()
+                .instance()
+                .set(&MatchingKey::GrantSources, &sources);
+        }
+
+        env.events()
+            .publish(("campaign", "grant_source_added"), source);
+    }
+
+    /// Accept a grant from an authorized outside funder, such as the factory
+    /// running a quadratic funding round.
+    ///
+    /// Grants count toward the goal but are not contributions: they mint no
+    /// receipts or shares and are returned to the funder if the campaign is
+    /// refunded or cancelled. Like contributions, they are accepted only
+    /// before the deadline and within the hard cap.
+    ///
+    /// # Errors
+    /// * `CampaignEnded`   – The deadline has passed.
+    /// * `HardCapExceeded` – The grant would push the total past the hard cap.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If `funder` was not added with `add_grant_source`.
+    /// * If `amount` is not positive.
+    pub fn add_grant(env: Env, funder: Address, amount: i128) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+        if env.ledger().timestamp() > deadline {
+            return Err(ContractError::CampaignEnded);
+        }
+
+        funder.require_auth();
+
+        if !Self::grant_sources(env.clone()).contains(&funder) {
+            panic!("grant source not authorized");
+        }
+
+        if amount <= 0 {
+            panic!("grant amount must be positive");
+        }
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let new_total = total.checked_add(amount).ok_or(ContractError::Overflow)?;
+        let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
+        if new_total > hard_cap {
+            return Err(ContractError::HardCapExceeded);
+        }
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &funder,
+            &env.current_contract_address(),
+            &amount,
+        );
+
+        let mut grants: Map<Address, i128> = env
+            .storage()
+            .instance()
+            .get(&MatchingKey::Grants)
+            .unwrap_or_else(|| Map::new(&env));
+        let prev = grants.get(funder.clone()).unwrap_or(0);
+        grants.set(
+            funder.clone(),
+            prev.checked_add(amount).ok_or(ContractError::Overflow)?,
+        );
+        env.storage().instance().set(&MatchingKey::Grants, &grants);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalRaised, &new_total);
+
+        env.events()
+            .publish(("campaign", "grant_received"), (funder, amount));
+
+        Ok(())
+    }
+
+    /// Set the referral bonus paid at `withdraw`, in basis points of referred
+    /// contributions — requires the `Treasurer` role, before the first
+    /// contribution.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If the campaign has already received contributions.
+    /// * If `bps` exceeds 10,000 (100%).
+    pub fn set_referral_bps(env: Env, caller: Address, bps: u32) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("referral bonus cannot change after contributions");
+        }
+
+        if bps > 10_000 {
+            panic!("referral bonus cannot exceed 100%");
+        }
+
+        env.storage()
+            .instance()
+            .set(&ReferralKey::ReferralBps, &bps);
+
+        env.events()
+            .publish(("campaign", "referral_bps_updated"), bps);
+    }
+
+    /// Set the price-feed contract used to value secondary tokens — requires
+    /// the `Treasurer` role, before the first contribution.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If the campaign has already received contributions.
+    pub fn set_price_feed(env: Env, caller: Address, feed: Address) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("price feed cannot change after contributions");
+        }
+
+        env.storage().instance().set(&DataKey::PriceFeed, &feed);
+
+        env.events()
+            .publish(("campaign", "price_feed_updated"), feed);
+    }
+
+    /// Accept an additional token through `contribute_token` — requires the
+    /// `Treasurer` role, before the first contribution.
+    ///
+    /// Secondary tokens cannot be combined with milestone escrow or a vested
+    /// payout, whose tranches and refunds are sized in the campaign token.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If the campaign has already received contributions.
+    /// * If `token` is the campaign token or already accepted.
+    /// * If the campaign holds funds in milestone escrow, streams its payout
+    ///   or is in share mode.
+    pub fn add_accepted_token(env: Env, caller: Address, token: Address) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("accepted tokens cannot change after contributions");
+        }
+
+        let primary: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let mut tokens = Self::accepted_tokens(env.clone());
+        if token == primary || tokens.contains(&token) {
+            panic!("token already accepted");
+        }
+
+        let escrow_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBps)
+            .unwrap_or(0);
+        if escrow_bps > 0 {
+            panic!("secondary tokens cannot be combined with escrow");
+        }
+        if env.storage().instance().has(&VestingKey::VestingTerms) {
+            panic!("secondary tokens cannot be combined with vesting");
+        }
+        if share_mode(&env) {
+            panic!("secondary tokens cannot be combined with share mode");
+        }
+
+        tokens.push_back(token.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::AcceptedTokens, &tokens);
+
+        env.events().publish(("campaign", "token_accepted"), token);
+    }
+
+    /// Gate contributions behind a Merkle allowlist, or lift the gate with
+    /// `None` — requires the `Owner` role.
+    ///
+    /// While a root is set, `contribute`, `contribute_token` and `pledge` are
+    /// rejected, due subscription pulls are skipped, and participants use
+    /// `contribute_with_proof`.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    pub fn set_allowlist_root(env: Env, caller: Address, root: Option<BytesN<32>>) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Owner);
+
+        match root {
+            Some(ref root) => env.storage().instance().set(&DataKey::AllowlistRoot, root),
+            None => env.storage().instance().remove(&DataKey::AllowlistRoot),
+        }
+
+        env.events()
+            .publish(("campaign", "allowlist_updated"), root);
+    }
+
+    /// Set the per-address maximum on contributions plus pledges — requires
+    /// the `Treasurer` role.
+    ///
+    /// `max_amount` is in goal units and `max_goal_bps` is a share of the
+    /// goal; pass 0 to leave either unset. Once the campaign has
+    /// contributions or pledges the effective limit may only be raised.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If either bound is negative or `max_goal_bps` exceeds 10,000.
+    /// * If the limit would drop after contributions or pledges.
+    pub fn set_contribution_limit(env: Env, caller: Address, max_amount: i128, max_goal_bps: u32) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        if max_amount < 0 || max_goal_bps > 10_000 {
+            panic!("invalid contribution limit");
+        }
+
+        let previous = effective_contribution_limit(&env);
+        let limit = ContributionLimit {
+            max_amount,
+            max_goal_bps,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::ContributionLimit, &limit);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let pledged: i128 = env
+            .storage()
+            .instance()
+            .get(&PledgeKey::TotalPledged)
+            .unwrap_or(0);
+        if total > 0 || pledged > 0 {
+            let lowered = match (previous, effective_contribution_limit(&env)) {
+                (None, Some(_)) => true,
+                (Some(old), Some(new)) => new < old,
+                _ => false,
+            };
+            if lowered {
+                panic!("contribution limit can only increase");
+            }
+        }
+
+        env.events()
+            .publish(("campaign", "contribution_limit_updated"), limit);
+    }
+
+    /// Configure the lock period and penalty for `withdraw_contribution` —
+    /// requires the `Treasurer` role, before the first contribution.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active or already has contributions.
+    /// * If `penalty_bps` exceeds 10,000 (100%).
+    pub fn set_withdrawal_terms(env: Env, caller: Address, lock_period: u64, penalty_bps: u32) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("withdrawal terms cannot change after contributions");
+        }
+
+        if penalty_bps > 10_000 {
+            panic!("penalty cannot exceed 100%");
+        }
+
+        let terms = WithdrawalTerms {
+            lock_period,
+            penalty_bps,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawalTerms, &terms);
+
+        env.events()
+            .publish(("campaign", "withdrawal_terms_updated"), terms);
+    }
+
+    /// Set how many seconds before the deadline pledges become locked
+    /// against new pledges, cancellation and amendment — requires the
+    /// `Treasurer` role, before the first contribution or pledge.
+    ///
+    /// The window freezes the pledge record, not the token allowance behind
+    /// it, so a pledger can still default by revoking the allowance. Before
+    /// collection starts each pledge only counts toward the goal up to the
+    /// allowance left to pull it through.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active or already has contributions or
+    ///   pledges.
+    pub fn set_pledge_lock_window(env: Env, caller: Address, window: u64) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let pledged: i128 = env
+            .storage()
+            .instance()
+            .get(&PledgeKey::TotalPledged)
+            .unwrap_or(0);
+        if total > 0 || pledged > 0 {
+            panic!("pledge lock window cannot change after contributions");
+        }
+
+        env.storage()
+            .instance()
+            .set(&PledgeKey::PledgeLockWindow, &window);
+
+        env.events()
+            .publish(("campaign", "pledge_lock_window_updated"), window);
+    }
+
+    /// Add a roadmap milestone that holds back `payout_bps` of the raised
+    /// funds in escrow — requires the `Owner` role.
+    ///
+    /// Escrow terms are fixed before the first contribution so backers know
+    /// them when they contribute. The combined payout across milestones may
+    /// not exceed 100%.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active or already has contributions.
+    /// * If the date is not in the future or `payout_bps` is zero.
+    /// * If the combined payout would exceed 10,000 basis points.
+    /// * If the campaign accepts secondary tokens.
+    pub fn add_milestone(
+        env: Env,
+        caller: Address,
+        date: u64,
+        description: String,
+        payout_bps: u32,
+    ) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Owner);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("escrow terms cannot change after contributions");
+        }
+
+        if date <= env.ledger().timestamp() {
+            panic!("date must be in the future");
+        }
+        if description.is_empty() {
+            panic!("description cannot be empty");
+        }
+        if payout_bps == 0 {
+            panic!("payout_bps must be greater than 0");
+        }
+        if !Self::accepted_tokens(env.clone()).is_empty() {
+            panic!("secondary tokens cannot be combined with escrow");
+        }
+        if env.storage().instance().has(&VestingKey::VestingTerms) {
+            panic!("vesting cannot be combined with escrow");
+        }
+
+        let escrow_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBps)
+            .unwrap_or(0);
+        let new_escrow_bps = escrow_bps + payout_bps;
+        if new_escrow_bps > 10_000 {
+            panic!("milestone payouts cannot exceed 100%");
+        }
+
+        let mut roadmap: Vec<RoadmapItem> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Roadmap)
+            .unwrap_or_else(|| Vec::new(&env));
+        roadmap.push_back(RoadmapItem {
+            date,
+            description: description.clone(),
+            payout_bps,
+            approved: false,
+            released: false,
+        });
+        env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+        env.storage()
+            .instance()
+            .set(&DataKey::EscrowBps, &new_escrow_bps);
+
+        env.events().publish(
+            ("campaign", "milestone_added"),
+            (roadmap.len() - 1, date, payout_bps),
+        );
+    }
+
+    /// Approve completion of an escrowed milestone — admin-only, and only
+    /// when the admin is not the creator.
+    ///
+    /// Backers can also approve a milestone through an `ApproveMilestone`
+    /// proposal, which is the only way while the creator holds the admin
+    /// role.
+    ///
+    /// # Panics
+    /// * If the admin is the creator.
+    /// * If `index` is not an escrowed milestone.
+    pub fn approve_milestone(env: Env, index: u32) {
+        require_migrated(&env);
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+        if admin == creator {
+            panic!("creator cannot approve own milestones");
+        }
+        admin.require_auth();
+
+        approve_milestone_item(&env, index);
+    }
+
+    /// Release an approved milestone's tranche to the creator.
+    ///
+    /// Only callable by the creator after a successful `withdraw`, once the
+    /// milestone's date has passed and its completion has been approved. The
+    /// platform fee applies to each tranche as it is released.
+    ///
+    /// Returns the tranche amount before fees.
+    ///
+    /// # Errors
+    /// * `MilestoneNotReady` – The date has not passed or it is not approved.
+    pub fn release_milestone(env: Env, index: u32) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Successful {
+            panic!("campaign is not successful");
+        }
+        let abandoned: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowAbandoned)
+            .unwrap_or(false);
+        if abandoned {
+            panic!("escrow abandoned");
+        }
+
+        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+        creator.require_auth();
+
+        let mut roadmap: Vec<RoadmapItem> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Roadmap)
+            .unwrap_or_else(|| Vec::new(&env));
+        let mut item = roadmap.get(index).expect("milestone not found");
+        if item.payout_bps == 0 {
+            panic!("milestone has no payout");
+        }
+        if item.released {
+            panic!("milestone already released");
+        }
+        if !item.approved || env.ledger().timestamp() < item.date {
+            return Err(ContractError::MilestoneNotReady);
+        }
+
+        let base: i128 = env.storage().instance().get(&DataKey::EscrowBase).unwrap();
+        let escrow_bps: u32 = env.storage().instance().get(&DataKey::EscrowBps).unwrap();
+        let released: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowReleased)
+            .unwrap_or(0);
+
+        item.released = true;
+        roadmap.set(index, item.clone());
+
+        // The final tranche sweeps up any rounding remainder.
+        let all_released = roadmap.iter().all(|m| m.payout_bps == 0 || m.released);
+        let tranche = if all_released {
+            base * escrow_bps as i128 / 10_000 - released
+        } else {
+            base.checked_mul(item.payout_bps as i128)
+                .expect("tranche calculation overflow")
+                / 10_000
+        };
+
+        env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+        env.storage()
+            .instance()
+            .set(&DataKey::EscrowReleased, &(released + tranche));
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        let creator_payout = deduct_platform_fee(&env, &token_client, tranche, base);
+        token_client.transfer(&env.current_contract_address(), &creator, &creator_payout);
+
+        env.events()
+            .publish(("campaign", "milestone_released"), (index, tranche));
+
+        Ok(tranche)
+    }
+
+    /// Stop releasing escrowed funds and open the unreleased remainder for
+    /// pro-rata refunds.
+    ///
+    /// The sponsor's matched funds and any grants are part of the escrow, so
+    /// their pro-rata share of the remainder is sent back to them here;
+    /// contributors claim theirs with `claim_escrow_refund`.
+    ///
+    /// The admin may do this at any time after `withdraw`. Anyone may do it
+    /// once an unreleased milestone is more than `MILESTONE_GRACE_PERIOD`
+    /// seconds past its date.
+    pub fn abandon_escrow(env: Env) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Successful || !env.storage().instance().has(&DataKey::EscrowBase) {
+            panic!("no escrow to abandon");
+        }
+        if env.storage().instance().has(&DataKey::EscrowAbandoned) {
+            panic!("escrow abandoned");
+        }
+
+        let roadmap: Vec<RoadmapItem> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Roadmap)
+            .unwrap_or_else(|| Vec::new(&env));
+        let now = env.ledger().timestamp();
+        let overdue = roadmap
+            .iter()
+            .any(|m| m.payout_bps > 0 && !m.released && now > m.date + MILESTONE_GRACE_PERIOD);
+        if !overdue {
+            let admin: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::Admin)
+                .expect("admin renounced");
+            admin.require_auth();
+        }
+
+        mark_escrow_abandoned(&env);
+    }
+
+    /// Refund a contributor's pro-rata share of the unreleased escrow after
+    /// `abandon_escrow`. Anyone may call this on behalf of a contributor.
+    ///
+    /// # Errors
+    /// * `EscrowNotAbandoned` – The escrow has not been abandoned.
+    /// * `AlreadyClaimed`     – The contributor's share was already paid out.
+    /// * `NothingToClaim`     – The address has no contribution on record.
+    pub fn claim_escrow_refund(env: Env, contributor: Address) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let abandoned: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowAbandoned)
+            .unwrap_or(false);
+        if !abandoned {
+            return Err(ContractError::EscrowNotAbandoned);
+        }
+
+        let shares = share_mode(&env);
+        let claimed_key = DataKey::RefundClaimed(contributor.clone());
+        if !shares && env.storage().persistent().has(&claimed_key) {
+            return Err(ContractError::AlreadyClaimed);
+        }
+
+        let contribution: i128 = if shares {
+            share_balance(&env, &contributor)
+        } else {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Contribution(contributor.clone()))
+                .unwrap_or(0)
+        };
+        if contribution <= 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+
+        let base: i128 = env.storage().instance().get(&DataKey::EscrowBase).unwrap();
+        let escrow_bps: u32 = env.storage().instance().get(&DataKey::EscrowBps).unwrap();
+        let released: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowReleased)
+            .unwrap_or(0);
+        let remaining = base * escrow_bps as i128 / 10_000 - released;
+        let amount = contribution
+            .checked_mul(remaining)
+            .ok_or(ContractError::Overflow)?
+            / base;
+
+        if shares {
+            burn_shares(&env, &contributor, contribution);
+        } else {
+            env.storage().persistent().set(&claimed_key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(&claimed_key, 100, 100);
+        }
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&env.current_contract_address(), &contributor, &amount);
+
+        env.events()
+            .publish(("campaign", "escrow_refund_claimed"), (contributor, amount));
+
+        Ok(amount)
+    }
+
+    /// Replace the platform fee schedule — admin-only.
+    ///
+    /// Every recipient of the current schedule must also authorize the
+    /// change, so a creator holding the admin role cannot cut or redirect
+    /// the platform's fee on their own.
+    ///
+    /// Once the campaign has contributions or pledges, the new schedule may
+    /// not charge more than the current one at any amount raised, so backers
+    /// never pay a higher fee than they saw when contributing.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If any rate exceeds 10,000 basis points, tiers do not ascend, the
+    ///   caps are inconsistent, or recipient shares do not total 10,000.
+    /// * If the schedule would raise the fee after contributions.
+    pub fn set_fee_schedule(env: Env, schedule: FeeSchedule) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
+
+        let current: Option<FeeSchedule> = env.storage().instance().get(&FeeKey::PlatformConfig);
+        if let Some(current) = &current {
+            for recipient in current.recipients.iter() {
+                recipient.address.require_auth();
+            }
+        }
+
+        validate_fee_schedule(&schedule);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        let pledged: i128 = env
+            .storage()
+            .instance()
+            .get(&PledgeKey::TotalPledged)
+            .unwrap_or(0);
+        if total > 0 || pledged > 0 {
+            let current = current.unwrap_or(FeeSchedule {
+                fee_bps: 0,
+                tiers: Vec::new(&env),
+                min_fee: 0,
+                max_fee: 0,
+                recipients: Vec::new(&env),
+            });
+            if !fee_schedule_within(&schedule, &current) {
+                panic!("fee cannot increase after contributions");
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set(&FeeKey::PlatformConfig, &schedule);
+
+        env.events()
+            .publish(("campaign", "fee_schedule_updated"), schedule);
+    }
+
+    /// Stream the creator's payout linearly after `withdraw` instead of
+    /// paying it at once — requires the `Treasurer` role.
+    ///
+    /// Nothing vests for `cliff` seconds; everything has vested `duration`
+    /// seconds after `withdraw`. Terms are fixed before the first
+    /// contribution so backers know them when they contribute.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active or already has contributions.
+    /// * If `duration` is zero or shorter than `cliff`.
+    /// * If the campaign holds funds in milestone escrow or accepts
+    ///   secondary tokens.
+    pub fn set_vesting(env: Env, caller: Address, cliff: u64, duration: u64) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Treasurer);
+
+        let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+        if total > 0 {
+            panic!("vesting terms cannot change after contributions");
+        }
+        if duration == 0 || cliff > duration {
+            panic!("invalid vesting schedule");
+        }
+        let escrow_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBps)
+            .unwrap_or(0);
+        if escrow_bps > 0 {
+            panic!("vesting cannot be combined with escrow");
+        }
+        if !Self::accepted_tokens(env.clone()).is_empty() {
+            panic!("vesting cannot be combined with secondary tokens");
+        }
+
+        let terms = VestingTerms { cliff, duration };
+        env.storage()
+            .instance()
+            .set(&VestingKey::VestingTerms, &terms);
+
+        env.events()
+            .publish(("campaign", "vesting_terms_updated"), terms);
+    }
+
+    /// Pay the creator everything vested but not yet claimed.
+    ///
+    /// Returns the amount paid.
+    ///
+    /// # Errors
+    /// * `NothingToClaim` – Nothing new has vested.
+    ///
+    /// # Panics
+    /// * If there is no payout stream.
+    pub fn claim_vested(env: Env) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let mut stream: VestingStream = env
+            .storage()
+            .instance()
+            .get(&VestingKey::Vesting)
+            .expect("no vesting stream");
+
+        let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+        creator.require_auth();
+
+        let amount = vested_amount(&env, &stream) - stream.claimed;
+        if amount <= 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+
+        stream.claimed += amount;
+        env.storage().instance().set(&VestingKey::Vesting, &stream);
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &creator,
+            &amount,
+        );
+
+        env.events()
+            .publish(("campaign", "vested_claimed"), (creator, amount));
+
+        Ok(amount)
+    }
+
+    /// Stop the creator's payout stream — admin-only.
+    ///
+    /// What has vested stays claimable by the creator; the locked remainder
+    /// is refunded pro-rata through
+    /// [`claim_vesting_refund`](Self::claim_vesting_refund). Backers can also
+    /// halt the stream through a `ForceRefund` proposal.
+    pub fn halt_vesting(env: Env) {
+        require_migrated(&env);
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("admin renounced");
+        admin.require_auth();
+
+        halt_vesting_stream(&env);
+    }
+
+    /// Refund a contributor's pro-rata share of the locked payout after the
+    /// stream was halted. Anyone may call this on behalf of a contributor.
+    ///
+    /// # Errors
+    /// * `VestingNotHalted` – There is no halted payout stream.
+    /// * `AlreadyClaimed`   – The contributor's share was already paid out.
+    /// * `NothingToClaim`   – The address has no contribution on record.
+    pub fn claim_vesting_refund(env: Env, contributor: Address) -> Result<i128, ContractError> {
+        require_migrated(&env);
+
+        let stream: Option<VestingStream> = env.storage().instance().get(&VestingKey::Vesting);
+        let stream = match stream {
+            Some(stream) if stream.halted_at.is_some() => stream,
+            _ => return Err(ContractError::VestingNotHalted),
+        };
+
+        let shares = share_mode(&env);
+        let claimed_key = VestingKey::VestingRefundClaimed(contributor.clone());
+        if !shares && env.storage().persistent().has(&claimed_key) {
+            return Err(ContractError::AlreadyClaimed);
+        }
+
+        let contribution: i128 = if shares {
+            share_balance(&env, &contributor)
+        } else {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Contribution(contributor.clone()))
+                .unwrap_or(0)
+        };
+        if contribution <= 0 {
+            return Err(ContractError::NothingToClaim);
+        }
+
+        let locked = stream.total - vested_amount(&env, &stream);
+        let amount = contribution
+            .checked_mul(locked)
+            .ok_or(ContractError::Overflow)?
+            / stream.base;
+
+        if shares {
+            burn_shares(&env, &contributor, contribution);
+        } else {
+            env.storage().persistent().set(&claimed_key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(&claimed_key, 100, 100);
+        }
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &contributor,
+            &amount,
+        );
+
+        env.events().publish(
+            ("campaign", "vesting_refund_claimed"),
+            (contributor, amount),
+        );
+
+        Ok(amount)
+    }
+
+    /// Open a proposal for backers to vote on.
+    ///
+    /// Only addresses with a contribution on record may propose. Voting runs
+    /// for `VOTING_PERIOD` seconds, weighted by each backer's contribution —
+    /// or share balance in share mode — as it stood when the proposal was
+    /// created.
+    ///
+    /// Returns the new proposal id.
+    ///
+    /// # Errors
+    /// * `NotABacker`     – The proposer has no contribution on record.
+    /// * `NoVotingWeight` – There is nothing left for backers to vote with,
+    ///   e.g. after a withdrawal without escrow or vesting.
+    pub fn create_proposal(
+        env: Env,
+        proposer: Address,
+        action: ProposalAction,
+    ) -> Result<u32, ContractError> {
+        require_migrated(&env);
+
+        proposer.require_auth();
+
+        if voting_weight(&env, &proposer) <= 0 {
+            return Err(ContractError::NotABacker);
+        }
+
+        // After `withdraw` TotalRaised is zeroed, so fall back to the amount
+        // the escrow or payout stream was sized against. Matches and grants
+        // count toward those totals but carry no votes.
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        let total_weight: i128 = if share_mode(&env) {
+            env.storage().instance().get(&ShareKey::Supply).unwrap_or(0)
+        } else if status == Status::Successful {
+            let stream: Option<VestingStream> = env.storage().instance().get(&VestingKey::Vesting);
+            let base: i128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::EscrowBase)
+                .or(stream.map(|stream| stream.base))
+                .unwrap_or(0);
+            if base > 0 {
+                base - outside_funds(&env)
+            } else {
+                0
+            }
+        } else {
+            let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+            total - outside_funds(&env)
+        };
+        if total_weight <= 0 {
+            return Err(ContractError::NoVotingWeight);
+        }
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0);
+        let proposal = Proposal {
+            id,
+            proposer: proposer.clone(),
+            action: action.clone(),
+            end: env.ledger().timestamp() + VOTING_PERIOD,
+            total_weight,
+            votes_for: 0,
+            votes_against: 0,
+            executed: false,
+        };
+
+        let key = DataKey::Proposal(id);
+        env.storage().persistent().set(&key, &proposal);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+        env.storage()
+            .instance()
+            .set(&DataKey::ProposalCount, &(id + 1));
+
+        env.events()
+            .publish(("campaign", "proposal_created"), (id, proposer, action));
+
+        Ok(id)
+    }
+
+    /// Vote on an open proposal with weight equal to the voter's
+    /// contribution, or share balance in share mode, when the proposal was
+    /// created. Each backer votes once per proposal.
+    ///
+    /// # Errors
+    /// * `VotingClosed` – The voting window has ended.
+    /// * `AlreadyVoted` – The voter already voted on this proposal.
+    /// * `NotABacker`   – The voter had no weight when the proposal was created.
+    pub fn vote(env: Env, voter: Address, id: u32, support: bool) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        voter.require_auth();
+
+        let key = DataKey::Proposal(id);
+        let mut proposal: Proposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("proposal not found");
+        if env.ledger().timestamp() > proposal.end {
+            return Err(ContractError::VotingClosed);
+        }
+
+        let vote_key = DataKey::Vote(id, voter.clone());
+        if env.storage().persistent().has(&vote_key) {
+            return Err(ContractError::AlreadyVoted);
+        }
+
+        let weight = weight_at(&env, &voter, id);
+        if weight <= 0 {
+            return Err(ContractError::NotABacker);
+        }
+
+        if support {
+            proposal.votes_for += weight;
+        } else {
+            proposal.votes_against += weight;
+        }
+        env.storage().persistent().set(&key, &proposal);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+        env.storage().persistent().set(&vote_key, &support);
+        env.storage().persistent().extend_ttl(&vote_key, 100, 100);
+
+        env.events()
+            .publish(("campaign", "voted"), (id, voter, support, weight));
+
+        Ok(())
+    }
+
+    /// Carry out a proposal's action once voting has closed — callable by
+    /// anyone.
+    ///
+    /// A proposal passes when the votes cast reach `PROPOSAL_QUORUM_BPS` of
+    /// its total weight and more weight voted for than against.
+    ///
+    /// # Errors
+    /// * `VotingOpen`       – The voting window has not ended.
+    /// * `ProposalRejected` – Quorum was missed or the vote failed.
+    pub fn execute_proposal(env: Env, id: u32) -> Result<(), ContractError> {
+        require_migrated(&env);
+
+        let key = DataKey::Proposal(id);
+        let mut proposal: Proposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("proposal not found");
+        if proposal.executed {
+            panic!("proposal already executed");
+        }
+        if env.ledger().timestamp() <= proposal.end {
+            return Err(ContractError::VotingOpen);
+        }
+
+        let turnout = proposal.votes_for + proposal.votes_against;
+        let quorum = proposal.total_weight * PROPOSAL_QUORUM_BPS / 10_000;
+        if turnout < quorum || proposal.votes_for <= proposal.votes_against {
+            return Err(ContractError::ProposalRejected);
+        }
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        match proposal.action {
+            ProposalAction::ApproveMilestone(index) => {
+                approve_milestone_item(&env, index);
+            }
+            ProposalAction::ExtendDeadline(new_deadline) => {
+                if status != Status::Active {
+                    panic!("campaign is not active");
+                }
+                let current_deadline: u64 =
+                    env.storage().instance().get(&DataKey::Deadline).unwrap();
+                if new_deadline <= current_deadline {
+                    panic!("new deadline must be after current deadline");
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::Deadline, &new_deadline);
+                env.events().publish(
+                    ("campaign", "deadline_updated"),
+                    (current_deadline, new_deadline),
+                );
+            }
+            ProposalAction::ForceRefund => match status {
+                Status::Active => {
+                    let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+                    env.storage().instance().set(&DataKey::TotalRaised, &0i128);
+                    env.storage()
+                        .instance()
+                        .set(&DataKey::Status, &Status::Refunded);
+                    // Drop any half-run settlement so no batch resumes from it.
+                    env.storage().instance().remove(&DataKey::RefundCursor);
+                    env.storage().instance().remove(&PledgeKey::PledgeCursor);
+                    env.storage().instance().remove(&PledgeKey::TotalCollected);
+                    return_matched_funds(&env);
+                    return_grants(&env);
+                    env.events().publish(("campaign", "refunded"), total);
+                }
+                Status::Successful if env.storage().instance().has(&DataKey::EscrowBase) => {
+                    mark_escrow_abandoned(&env);
+                }
+                Status::Successful if env.storage().instance().has(&VestingKey::Vesting) => {
+                    halt_vesting_stream(&env);
+                }
+                _ => panic!("nothing to refund"),
+            },
+        }
+
+        proposal.executed = true;
+        env.storage().persistent().set(&key, &proposal);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        env.events().publish(("campaign", "proposal_executed"), id);
+
+        Ok(())
+    }
+
+    /// Returns a proposal with its current tally.
+    pub fn proposal(env: Env, id: u32) -> Proposal {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Proposal(id))
+            .expect("proposal not found")
+    }
+
+    /// Returns all proposals whose voting window is still open.
+    pub fn open_proposals(env: Env) -> Vec<Proposal> {
+        let count: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0);
+        let now = env.ledger().timestamp();
+        let mut open = Vec::new(&env);
+        for id in 0..count {
+            if let Some(p) = env
+                .storage()
+                .persistent()
+                .get::<_, Proposal>(&DataKey::Proposal(id))
+            {
+                if now <= p.end {
+                    open.push_back(p);
+                }
+            }
+        }
+        open
+    }
+
+    /// Returns how `voter` voted on a proposal, or None if they did not vote.
+    pub fn vote_of(env: Env, id: u32, voter: Address) -> Option<bool> {
+        env.storage().persistent().get(&DataKey::Vote(id, voter))
+    }
+
+    /// Returns the milestone escrow configuration and progress.
+    pub fn escrow_info(env: Env) -> EscrowInfo {
+        let total_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBps)
+            .unwrap_or(0);
+        let base: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowBase)
+            .unwrap_or(0);
+        EscrowInfo {
+            total_bps,
+            base,
+            held: base * total_bps as i128 / 10_000,
+            released: env
+                .storage()
+                .instance()
+                .get(&DataKey::EscrowReleased)
+                .unwrap_or(0),
+            abandoned: env
+                .storage()
+                .instance()
+                .get(&DataKey::EscrowAbandoned)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns the platform fee schedule, if one is set.
+    pub fn fee_schedule(env: Env) -> Option<FeeSchedule> {
+        env.storage().instance().get(&FeeKey::PlatformConfig)
+    }
+
+    /// Returns the platform fee, its split and the creator payout if the
+    /// campaign withdrew `total` in the campaign token.
+    pub fn preview_fees(env: Env, total: i128) -> FeePreview {
+        let schedule: Option<FeeSchedule> = env.storage().instance().get(&FeeKey::PlatformConfig);
+        let Some(schedule) = schedule else {
+            return FeePreview {
+                fee_bps: 0,
+                fee: 0,
+                creator_payout: total,
+                shares: Vec::new(&env),
+            };
+        };
+
+        let fee = platform_fee(&schedule, total, total, Some(0));
+        FeePreview {
+            fee_bps: fee_bps_at(&schedule, total),
+            fee,
+            creator_payout: total - fee,
+            shares: fee_shares(&env, &schedule, fee),
+        }
+    }
+
+    /// Returns the creator payout vesting schedule, if one is set.
+    pub fn vesting_terms(env: Env) -> Option<VestingTerms> {
+        env.storage().instance().get(&VestingKey::VestingTerms)
+    }
+
+    /// Returns the vested, claimed and locked balances of the creator's
+    /// payout stream, all zero before `withdraw`.
+    pub fn vesting_info(env: Env) -> VestingInfo {
+        let stream: Option<VestingStream> = env.storage().instance().get(&VestingKey::Vesting);
+        match stream {
+            Some(stream) => {
+                let vested = vested_amount(&env, &stream);
+                VestingInfo {
+                    vested,
+                    claimed: stream.claimed,
+                    locked: stream.total - vested,
+                    halted: stream.halted_at.is_some(),
+                }
+            }
+            None => VestingInfo {
+                vested: 0,
+                claimed: 0,
+                locked: 0,
+                halted: false,
+            },
+        }
+    }
+
+    /// Add a stretch goal milestone to the campaign.
+    ///
+    /// Requires the `Editor` role. The milestone must be greater than the
+    /// primary goal.
+    pub fn add_stretch_goal(env: Env, caller: Address, milestone: i128) {
+        require_migrated(&env);
+
+        require_role(&env, &caller, Role::Editor);
+
+        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+        if milestone <= goal {
+            panic!("stretch goal must be greater than primary goal");
+        }
+
+        let mut stretch_goals: Vec<i128> = env
+            .storage()
+            .instance()
+            .get(&DataKey::StretchGoals)
+            .unwrap_or_else(|| Vec::new(&env));
+
+        stretch_goals.push_back(milestone);
+        env.storage()
+            .instance()
+            .set(&DataKey::StretchGoals, &stretch_goals);
+    }
+
+    /// Add a reward tier (`Editor` role). Rejects min_amount <= 0.
+    pub fn add_reward_tier(env: Env, caller: Address, name: String, min_amount: i128) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Editor);
+
+        if min_amount <= 0 {
+            panic!("min_amount must be greater than 0");
+        }
+
+        let mut tiers: Vec<RewardTier> = env
+            .storage()
+            .instance()
+            .get(&DataKey::RewardTiers)
+            .unwrap_or_else(|| Vec::new(&env));
+
+        tiers.push_back(RewardTier {
+            name: name.clone(),
+            min_amount,
+            supply: None,
+            claimed: 0,
+            opens_at: None,
+            closes_at: None,
+        });
+        env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+
+        env.events()
+            .publish(("campaign", "reward_tier_added"), (name, min_amount));
+    }
+
+    /// Add a reward tier with a fixed number of slots and an optional
+    /// availability window (`Editor` role).
+    ///
+    /// Backers reserve a slot with `contribute_for_tier`.
+    ///
+    /// # Panics
+    /// * If the campaign is not Active.
+    /// * If `min_amount` or `supply` is zero, or the window is empty.
+    pub fn add_limited_reward_tier(
+        env: Env,
+        caller: Address,
+        name: String,
+        min_amount: i128,
+        supply: u32,
+        opens_at: Option<u64>,
+        closes_at: Option<u64>,
+    ) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Active {
+            panic!("campaign is not active");
+        }
+
+        require_role(&env, &caller, Role::Editor);
+
+        if min_amount <= 0 {
+            panic!("min_amount must be greater than 0");
+        }
+        if supply == 0 {
+            panic!("supply must be greater than 0");
+        }
+        if let (Some(opens), Some(closes)) = (opens_at, closes_at) {
+            if closes <= opens {
+                panic!("tier window is empty");
+            }
+        }
+
+        let mut tiers: Vec<RewardTier> = env
+            .storage()
+            .instance()
+            .get(&DataKey::RewardTiers)
+            .unwrap_or_else(|| Vec::new(&env));
+
+        tiers.push_back(RewardTier {
+            name: name.clone(),
+            min_amount,
+            supply: Some(supply),
+            claimed: 0,
+            opens_at,
+            closes_at,
+        });
+        env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+
+        env.events().publish(
+            ("campaign", "reward_tier_added"),
+            (name, min_amount, supply),
+        );
+    }
+
+    /// Transfer a contribution receipt — callable by its current owner.
+    ///
+    /// # Panics
+    /// * If `from` does not own the receipt.
+    /// * If the receipt is void.
+    pub fn transfer_receipt(env: Env, from: Address, to: Address, id: u64) {
+        require_migrated(&env);
+
+        from.require_auth();
+
+        let mut receipt = Self::receipt(env.clone(), id);
+        if receipt.owner != from {
+            panic!("not receipt owner");
+        }
+        if receipt.void {
+            panic!("receipt is void");
+        }
+
+        receipt.owner = to.clone();
+        let receipt_key = ReceiptKey::Receipt(id);
+        env.storage().persistent().set(&receipt_key, &receipt);
+        env.storage()
+            .persistent()
+            .extend_ttl(&receipt_key, 100, 100);
+
+        let mut held = Self::receipts_of(env.clone(), from.clone());
+        if let Some(index) = held.first_index_of(id) {
+            held.remove(index);
+        }
+        set_receipt_list(&env, ReceiptKey::ReceiptsOf(from.clone()), &held);
+
+        let mut received = Self::receipts_of(env.clone(), to.clone());
+        received.push_back(id);
+        set_receipt_list(&env, ReceiptKey::ReceiptsOf(to.clone()), &received);
+
+        env.events()
+            .publish(("campaign", "receipt_transferred"), (from, to, id));
+    }
+
+    /// Record that rewards were shipped to a batch of backers (`Editor`
+    /// role), with a hash of the shipment proof (e.g. tracking manifest).
+    ///
+    /// Backers without a reward or who already confirmed receipt are
+    /// skipped. Disputed rewards may be re-shipped.
+    ///
+    /// Returns the number of backers marked as shipped.
+    ///
+    /// # Panics
+    /// * If the campaign has not been withdrawn successfully.
+    pub fn mark_shipped(
+        env: Env,
+        caller: Address,
+        backers: Vec<Address>,
+        proof: BytesN<32>,
+    ) -> u32 {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Successful {
+            panic!("campaign is not successful");
+        }
+
+        require_role(&env, &caller, Role::Editor);
+
+        let mut marked = 0u32;
+        for backer in backers.iter() {
+            match Self::fulfillment_status(env.clone(), backer.clone()) {
+                None | Some(FulfillmentStatus::Confirmed) => continue,
+                Some(_) => {}
+            }
+
+            let key = DataKey::Fulfillment(backer.clone());
+            env.storage()
+                .persistent()
+                .set(&key, &FulfillmentStatus::Shipped(proof.clone()));
+            env.storage().persistent().extend_ttl(&key, 100, 100);
+
+            env.events()
+                .publish(("campaign", "reward_shipped"), (backer, proof.clone()));
+            marked += 1;
+        }
+
+        marked
+    }
+
+    /// Confirm receipt of a shipped reward — callable by the backer.
+    ///
+    /// # Panics
+    /// * If the backer's reward is not in the Shipped state.
+    pub fn confirm_delivery(env: Env, backer: Address) {
+        require_migrated(&env);
+
+        backer.require_auth();
+
+        match Self::fulfillment_status(env.clone(), backer.clone()) {
+            Some(FulfillmentStatus::Shipped(_)) => {}
+            _ => panic!("reward not shipped"),
+        }
+
+        let key = DataKey::Fulfillment(backer.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &FulfillmentStatus::Confirmed);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        env.events()
+            .publish(("campaign", "reward_confirmed"), backer);
+    }
+
+    /// Dispute a reward that was not delivered or arrived wrong — callable
+    /// by the backer once the campaign has been withdrawn.
+    ///
+    /// # Panics
+    /// * If the campaign has not been withdrawn successfully.
+    /// * If the backer has no reward or already confirmed it.
+    pub fn dispute_delivery(env: Env, backer: Address) {
+        require_migrated(&env);
+
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Successful {
+            panic!("campaign is not successful");
+        }
+
+        backer.require_auth();
+
+        match Self::fulfillment_status(env.clone(), backer.clone()) {
+            Some(FulfillmentStatus::Pending) | Some(FulfillmentStatus::Shipped(_)) => {}
+            Some(FulfillmentStatus::Disputed) => panic!("reward already disputed"),
+            _ => panic!("no reward to dispute"),
+        }
+
+        let key = DataKey::Fulfillment(backer.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &FulfillmentStatus::Disputed);
+        env.storage().persistent().extend_ttl(&key, 100, 100);
+
+        env.events()
+            .publish(("campaign", "reward_disputed"), backer);
+    }
+
+    /// Returns the full ordered list of reward tiers, including how many
+    /// slots of each limited tier have been claimed.
+    pub fn reward_tiers(env: Env) -> Vec<RewardTier> {
+        env.storage()
+            .instance()
+            .get(&DataKey::RewardTiers)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns the remaining slots in a limited tier, or None for an
+    /// unlimited tier.
+    pub fn tier_remaining(env: Env, tier_id: u32) -> Option<u32> {
+        let tiers = Self::reward_tiers(env);
+        let tier = tiers.get(tier_id).expect("tier not found");
+        tier.supply.map(|supply| supply - tier.claimed)
+    }
+
+    /// Returns the index of the tier the user reserved with
+    /// `contribute_for_tier`, if any.
+    pub fn selected_tier(env: Env, user: Address) -> Option<u32> {
+        env.storage().persistent().get(&DataKey::SelectedTier(user))
+    }
+
+    /// Returns the user's reward tier name, or None if the user has not
+    /// contributed or no tiers are defined.
+    ///
+    /// A tier reserved with `contribute_for_tier` always wins. Otherwise the
+    /// highest unlimited tier the contribution, or in share mode the share
+    /// balance, qualifies for is returned.
+    pub fn get_user_tier(env: Env, user: Address) -> Option<String> {
+        let tier_id = user_tier_index(&env, &user)?;
+        Self::reward_tiers(env).get(tier_id).map(|t| t.name)
+    }
+
+    /// Returns a backer's reward fulfillment state, or None if the backer
+    /// does not qualify for a reward tier.
+    pub fn fulfillment_status(env: Env, backer: Address) -> Option<FulfillmentStatus> {
+        user_tier_index(&env, &backer)?;
+        Some(
+            env.storage()
+                .persistent()
+                .get(&DataKey::Fulfillment(backer))
+                .unwrap_or(FulfillmentStatus::Pending),
+        )
+    }
+
+    /// Returns fulfillment progress for each reward tier, in tier order.
+    ///
+    /// Iterates every contributor, or in share mode every address that has
+    /// held shares, since tiers follow the shares. Meant for off-chain reads.
+    pub fn fulfillment_summary(env: Env) -> Vec<FulfillmentSummary> {
+        let tiers = Self::reward_tiers(env.clone());
+        let mut summary: Vec<FulfillmentSummary> = Vec::new(&env);
+        for _ in tiers.iter() {
+            summary.push_back(FulfillmentSummary {
+                pending: 0,
+                shipped: 0,
+                confirmed: 0,
+                disputed: 0,
+            });
+        }
+
+        let backers: Vec<Address> = if share_mode(&env) {
+            share_holders(&env)
+        } else {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Contributors)
+                .unwrap_or_else(|| Vec::new(&env))
+        };
+        for backer in backers.iter() {
+            let Some(tier_id) = user_tier_index(&env, &backer) else {
+                continue;
+            };
+            let mut counts = summary.get(tier_id).unwrap();
+            match Self::fulfillment_status(env.clone(), backer).unwrap() {
+                FulfillmentStatus::Pending => counts.pending += 1,
+                FulfillmentStatus::Shipped(_) => counts.shipped += 1,
+                FulfillmentStatus::Confirmed => counts.confirmed += 1,
+                FulfillmentStatus::Disputed => counts.disputed += 1,
+            }
+            summary.set(tier_id, counts);
+        }
+
+        summary
+    }
+
+    /// Returns the next unmet stretch goal milestone.
+    ///
+    /// Returns 0 if there are no stretch goals or all have been met.
+    pub fn current_milestone(env: Env) -> i128 {
+        let total_raised: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalRaised)
+            .unwrap_or(0);
+
+        let stretch_goals: Vec<i128> = env
+            .storage()
+            .instance()
+            .get(&DataKey::StretchGoals)
+            .unwrap_or_else(|| Vec::new(&env));
+
+        for milestone in stretch_goals.iter() {
+            if total_raised < milestone {
+                return milestone;
+            }
+        }
+
+        0
+    }
+    pub fn total_raised(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalRaised)
+            .unwrap_or(0)
+    }
+
+    /// Returns the funding goal.
+    pub fn goal(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::Goal).unwrap()
+    }
+
+    /// Returns the hard cap (maximum total that can be raised).
+    pub fn hard_cap(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::HardCap).unwrap()
+    }
+
+    /// Returns the lock period and penalty applied by `withdraw_contribution`.
+    pub fn withdrawal_terms(env: Env) -> WithdrawalTerms {
+        env.storage()
+            .instance()
+            .get(&DataKey::WithdrawalTerms)
+            .unwrap_or(WithdrawalTerms {
+                lock_period: 0,
+                penalty_bps: 0,
+            })
+    }
+
+    /// Returns how contributions past the hard cap are handled.
+    pub fn hard_cap_policy(env: Env) -> HardCapPolicy {
+        env.storage()
+            .instance()
+            .get(&DataKey::HardCapPolicy)
+            .unwrap_or(HardCapPolicy::Truncate)
+    }
+
+    /// Returns the campaign deadline.
+    pub fn deadline(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::Deadline).unwrap()
+    }
+
+    /// Returns the contribution of a specific address.
+    pub fn contribution(env: Env, contributor: Address) -> i128 {
+        let contribution_key = DataKey::Contribution(contributor);
+        env.storage()
+            .persistent()
+            .get(&contribution_key)
+            .unwrap_or(0)
+    }
+
+    /// Returns a contributor's raw contribution in one secondary token.
+    pub fn token_contribution(env: Env, contributor: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenContribution(contributor, token))
+            .map(|b: TokenBalance| b.amount)
+            .unwrap_or(0)
+    }
+
+    /// Returns the raw amount raised in one secondary token, not yet paid
+    /// out.
+    pub fn token_raised(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenRaised(token))
+            .map(|b: TokenBalance| b.amount)
+            .unwrap_or(0)
+    }
+
+    /// Returns the secondary tokens accepted through `contribute_token`.
+    pub fn accepted_tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AcceptedTokens)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns a contribution receipt.
+    ///
+    /// Every receipt reads as void once the campaign is Refunded or
+    /// Cancelled.
+    pub fn receipt(env: Env, id: u64) -> Receipt {
+        let mut receipt: Receipt = env
+            .storage()
+            .persistent()
+            .get(&ReceiptKey::Receipt(id))
+            .expect("receipt not found");
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status == Status::Refunded || status == Status::Cancelled {
+            receipt.void = true;
+        }
+        receipt
+    }
+
+    /// Returns the current owner of a receipt.
+    pub fn owner_of(env: Env, id: u64) -> Address {
+        Self::receipt(env, id).owner
+    }
+
+    /// Returns the ids of receipts held by `owner`.
+    pub fn receipts_of(env: Env, owner: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&ReceiptKey::ReceiptsOf(owner))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns the sponsor matching pool, if one was funded.
+    pub fn matching_status(env: Env) -> Option<MatchingPool> {
+        env.storage().instance().get(&MatchingKey::MatchingPool)
+    }
+
+    /// Returns every address with a recorded contribution, in first-contribution
+    /// order.
+    pub fn contributors(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Contributors)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns a backer's subscription, if any.
+    pub fn subscription(env: Env, backer: Address) -> Option<Subscription> {
+        env.storage()
+            .persistent()
+            .get(&SubscriptionKey::Subscription(backer))
+    }
+
+    /// Returns when a backer's next subscription payment is due.
+    pub fn next_payment_due(env: Env, backer: Address) -> Option<u64> {
+        Self::subscription(env, backer).map(|subscription| subscription.next_due)
+    }
+
+    /// Returns every address that has subscribed, in the order
+    /// `process_subscriptions` pages through them. Cancelled subscriptions
+    /// keep their slot; `subscription` returns None for them.
+    pub fn subscribers(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&SubscriptionKey::Subscribers)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns the outside grants received, keyed by funder.
+    pub fn grants(env: Env) -> Map<Address, i128> {
+        env.storage()
+            .instance()
+            .get(&MatchingKey::Grants)
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Returns the addresses allowed to send grants.
+    pub fn grant_sources(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&MatchingKey::GrantSources)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns the matching funds committed against a contributor.
+    pub fn matched_amount(env: Env, contributor: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&MatchingKey::Matched(contributor))
+            .unwrap_or(0)
+    }
+
+    /// Returns the referral bonus in basis points.
+    pub fn referral_bps(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ReferralKey::ReferralBps)
+            .unwrap_or(0)
+    }
+
+    /// Returns a contributor's referral attribution, if any.
+    pub fn referral_of(env: Env, contributor: Address) -> Option<ReferralRecord> {
+        env.storage()
+            .persistent()
+            .get(&ReferralKey::Referral(contributor))
+    }
+
+    /// Returns every address that has referred a contribution.
+    pub fn referrers(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&ReferralKey::Referrers)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns a referrer's attributed contributions and earned bonus.
+    pub fn referral_stats(env: Env, referrer: Address) -> ReferralStats {
+        let referred: i128 = env
+            .storage()
+            .persistent()
+            .get(&ReferralKey::ReferredTotal(referrer.clone()))
+            .unwrap_or(0);
+        let earned: i128 = env
+            .storage()
+            .persistent()
+            .get(&ReferralKey::ReferralEarnings(referrer.clone()))
+            .unwrap_or(0);
+        ReferralStats {
+            referrer,
+            referred,
+            earned,
+        }
+    }
+
+    /// Returns the bonus paid to a referrer at `withdraw`.
+    pub fn referral_earnings(env: Env, referrer: Address) -> i128 {
+        Self::referral_stats(env, referrer).earned
+    }
+
+    /// Returns the top `LEADERBOARD_SIZE` referrers by attributed
+    /// contributions, highest first.
+    ///
+    /// The board is kept up to date as totals change. A referrer that drops
+    /// off it after a withdrawal by one of its backers only returns once its
+    /// own total changes again; use `referrers` and `referral_stats` for an
+    /// exhaustive ranking.
+    pub fn referral_leaderboard(env: Env) -> Vec<ReferralStats> {
+        let mut board: Vec<ReferralStats> = Vec::new(&env);
+        for referrer in leaderboard(&env).iter() {
+            board.push_back(Self::referral_stats(env.clone(), referrer));
+        }
+        board
+    }
+
+    /// Returns whether contributions mint share tokens.
+    pub fn share_mode(env: Env) -> bool {
+        share_mode(&env)
+    }
+
+    /// Returns the total share token supply.
+    pub fn share_supply(env: Env) -> i128 {
+        env.storage().instance().get(&ShareKey::Supply).unwrap_or(0)
+    }
+
+    /// Returns the allowlist Merkle root, if contributions are gated.
+    pub fn allowlist_root(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&DataKey::AllowlistRoot)
+    }
+
+    /// Returns the price-feed contract, if one is configured.
+    pub fn price_feed(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PriceFeed)
+    }
+
+    /// Returns the amount a contributor can currently reclaim with
+    /// `claim_refund`, in goal units, or 0 if the campaign is not refundable
+    /// or the refund was already claimed.
+    pub fn refundable(env: Env, contributor: Address) -> i128 {
+        let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+        if status != Status::Refunded && status != Status::Cancelled {
+            return 0;
+        }
+        refund_entitlement(&env, &contributor)
+    }
+
+    /// Returns the pledge of a specific address.
+    pub fn pledge_amount(env: Env, pledger: Address) -> i128 {
+        let pledge_key = PledgeKey::Pledge(pledger);
+        env.storage().persistent().get(&pledge_key).unwrap_or(0)
+    }
+
+    /// Returns how many seconds before the deadline pledges become locked.
+    pub fn pledge_lock_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&PledgeKey::PledgeLockWindow)
+            .unwrap_or(0)
+    }
+
+    /// Returns where a pledger's pledge stands, or None if they never pledged.
+    pub fn pledge_status(env: Env, pledger: Address) -> Option<PledgeStatus> {
+        if Self::pledge_amount(env.clone(), pledger.clone()) > 0 {
+            return Some(PledgeStatus::Pending);
+        }
+        if Self::defaulted_pledge(env.clone(), pledger.clone()) > 0 {
+            return Some(PledgeStatus::Defaulted);
+        }
+        if let Some(capped) = Self::capped_pledge(env.clone(), pledger.clone()) {
+            return Some(if capped.collected > 0 {
+                PledgeStatus::PartiallyCollected
+            } else {
+                PledgeStatus::CappedOut
+            });
+        }
+        let pledgers: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::Pledgers)
+            .unwrap_or_else(|| Vec::new(&env));
+        pledgers
+            .contains(&pledger)
+            .then_some(PledgeStatus::Collected)
+    }
+
+    /// Returns the pledge amount that could not be collected from an address.
+    pub fn defaulted_pledge(env: Env, pledger: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&PledgeKey::PledgeDefault(pledger))
+            .unwrap_or(0)
+    }
+
+    /// Returns how much of a pledge was and was not collected, if the hard
+    /// cap cut it short.
+    pub fn capped_pledge(env: Env, pledger: Address) -> Option<CappedPledge> {
+        env.storage()
+            .persistent()
+            .get(&PledgeKey::PledgeCapped(pledger))
+    }
+
+    /// Returns the total pledge amount that could not be collected.
+    pub fn total_defaulted(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&PledgeKey::TotalDefaulted)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total amount pledged (not yet transferred).
+    pub fn total_pledged(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&PledgeKey::TotalPledged)
+            .unwrap_or(0)
+    }
+
+    /// Returns the minimum contribution amount.
+    pub fn min_contribution(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinContribution)
+            .unwrap()
+    }
+
+    /// Returns the most a single address may contribute and pledge in total,
+    /// or None if there is no limit.
+    pub fn max_contribution(env: Env) -> Option<i128> {
+        effective_contribution_limit(&env)
+    }
+
+    /// Returns the configured per-address contribution limit.
+    pub fn contribution_limit(env: Env) -> ContributionLimit {
+        env.storage()
+            .instance()
+            .get(&DataKey::ContributionLimit)
+            .unwrap_or(ContributionLimit {
+                max_amount: 0,
+                max_goal_bps: 0,
+            })
+    }
+
+    /// Returns the primary campaign category.
+    pub fn category(env: Env) -> soroban_sdk::String {
+        env.storage().instance().get(&DataKey::Category).unwrap()
+    }
+
+    /// Returns the optional descriptive tags.
+    pub fn tags(env: Env) -> Vec<soroban_sdk::String> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Tags)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns comprehensive campaign statistics.
+    pub fn get_stats(env: Env) -> CampaignStats {
+        let total_raised: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalRaised)
+            .unwrap_or(0);
+        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+        let contributors: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Contributors)
+            .unwrap();
+
+        let progress_bps = if goal > 0 {
+            let raw = (total_raised * 10_000) / goal;
+            if raw > 10_000 {
+                10_000
+            } else {
+                raw as u32
+            }
+        } else {
+            0
+        };
+
+        let contributor_count = contributors.len();
+        let (average_contribution, largest_contribution) = if contributor_count == 0 {
+            (0, 0)
+        } else {
+            let average = total_raised / contributor_count as i128;
+            let mut largest = 0i128;
+            for contributor in contributors.iter() {
+                let amount: i128 = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::Contribution(contributor))
+                    .unwrap_or(0);
+                if amount > largest {
+                    largest = amount;
+                }
+            }
+            (average, largest)
+        };
+
+        let mut raised_by_token = Map::new(&env);
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        raised_by_token.set(token_address, primary_raised(&env, total_raised));
+        for token in Self::accepted_tokens(env.clone()).iter() {
+            raised_by_token.set(token.clone(), Self::token_raised(env.clone(), token));
+        }
+
+        CampaignStats {
+            total_raised,
+            goal,
+            progress_bps,
+            contributor_count,
+            average_contribution,
+            largest_contribution,
+            raised_by_token,
+        }
+    }
+
+    /// Returns the campaign title.
+    pub fn title(env: Env) -> String {
+        let empty = String::from_str(&env, "");
+        env.storage()
+            .instance()
+            .get(&DataKey::Title)
+            .unwrap_or(empty)
+    }
+
+    /// Returns the campaign description.
+    pub fn description(env: Env) -> String {
+        let empty = String::from_str(&env, "");
+        env.storage()
+            .instance()
+            .get(&DataKey::Description)
+            .unwrap_or(empty)
+    }
+
+    /// Returns the campaign social links.
+    pub fn socials(env: Env) -> String {
+        let empty = String::from_str(&env, "");
+        env.storage()
+            .instance()
+            .get(&DataKey::SocialLinks)
+            .unwrap_or(empty)
+    }
+
+    /// Returns the contract version.
+    ///
+    /// This view function allows external tools to detect which version of the
+    /// contract logic is currently running at this address. The version must be
+    /// manually incremented with every contract upgrade (see Issue #38).
+    pub fn version(_env: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+
+    /// Returns the version of the stored data layout.
+    pub fn storage_version(env: Env) -> u32 {
+        stored_version(&env)
+    }
+
+    /// Returns whether `account` holds `role`. Does not treat `Owner` as
+    /// implying other roles.
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        env.storage().instance().has(&DataKey::Role(role, account))
+    }
+
+    /// Returns the current admin, or None if the role was renounced.
+    pub fn admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+
+    /// Returns the upgrade announced via `schedule_upgrade`, if any.
+    pub fn pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        env.storage().instance().get(&DataKey::PendingUpgrade)
+    }
+
+    /// Returns the admin proposed via `propose_admin`, if any.
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Returns the token contract address used for contributions.
+    pub fn token(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Token).unwrap()
+    }
+}
+
+// ── Share Token ─────────────────────────────────────────────────────────────
+
+/// SEP-41 interface for campaign shares. Only usable in share mode.
+#[contractimpl]
+impl token::TokenInterface for CrowdfundContract {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        share_allowance(&env, &from, &spender)
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        require_share_mode(&env);
+        from.require_auth();
+
+        if amount < 0 {
+            panic!("negative amount");
+        }
+        let ledger = env.ledger().sequence();
+        if amount > 0 && expiration_ledger < ledger {
+            panic!("expiration_ledger is in the past");
+        }
+
+        let key = ShareKey::Allowance(from.clone(), spender.clone());
+        env.storage().temporary().set(
+            &key,
+            &ShareAllowance {
+                amount,
+                expiration_ledger,
+            },
+        );
+        if amount > 0 {
+            let live_for = expiration_ledger - ledger;
+            env.storage()
+                .temporary()
+                .extend_ttl(&key, live_for, live_for);
+        }
+
+        env.events()
+            .publish(("approve", from, spender), (amount, expiration_ledger));
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        share_balance(&env, &id)
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        require_share_mode(&env);
+        from.require_auth();
+
+        move_shares(&env, &from, &to, amount);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        require_share_mode(&env);
+        spender.require_auth();
+
+        spend_share_allowance(&env, &from, &spender, amount);
+        move_shares(&env, &from, &to, amount);
+    }
+
+    /// Burning shares forfeits their claim on refunds and the escrow; the
+    /// backing tokens stay with the campaign.
+    fn burn(env: Env, from: Address, amount: i128) {
+        require_share_mode(&env);
+        from.require_auth();
+
+        burn_shares(&env, &from, amount);
+    }
+
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        require_share_mode(&env);
+        spender.require_auth();
+
+        spend_share_allowance(&env, &from, &spender, amount);
+        burn_shares(&env, &from, amount);
+    }
+
+    fn decimals(env: Env) -> u32 {
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&env, &token_address).decimals()
+    }
+
+    fn name(env: Env) -> String {
+        share_token_info(&env).name
+    }
+
+    fn symbol(env: Env) -> String {
+        share_token_info(&env).symbol
+    }
+}
+
+// ── Internal Helpers ────────────────────────────────────────────────────────
+
+/// Returns the stored layout version. Campaigns initialized before the
+/// version was tracked report 0.
+fn stored_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StorageVersion)
+        .unwrap_or(0)
+}
+
+/// Aborts with `MigrationPending` if `migrate` has not caught storage up to
+/// this code version.
+fn require_migrated(env: &Env) {
+    if stored_version(env) < CONTRACT_VERSION {
+        panic_with_error!(env, ContractError::MigrationPending);
+    }
+}
+
+/// Requires `caller`'s authorization and that it holds `role` or `Owner`.
+fn require_role(env: &Env, caller: &Address, role: Role) {
+    let storage = env.storage().instance();
+    if !storage.has(&DataKey::Role(role, caller.clone()))
+        && !storage.has(&DataKey::Role(Role::Owner, caller.clone()))
+    {
+        panic!("not authorized");
+    }
+    caller.require_auth();
+}
+
+/// Migrates storage from layout `from` to layout `from + 1`.
+fn run_migration_step(env: &Env, from: u32) {
+    match from {
+        // Pre-migration campaigns never stored a hard cap, over-cap policy or
+        // admin. Fall back to the defaults `initialize` now writes.
+        0 => {
+            if !env.storage().instance().has(&DataKey::HardCap) {
+                let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+                env.storage().instance().set(&DataKey::HardCap, &goal);
+            }
+            if !env.storage().instance().has(&DataKey::HardCapPolicy) {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::HardCapPolicy, &HardCapPolicy::Truncate);
+            }
+            if !env.storage().instance().has(&DataKey::Admin) {
+                let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+                env.storage().instance().set(&DataKey::Admin, &creator);
+            }
+        }
+        // Privileged calls moved from a hard-wired creator check to roles.
+        1 => {
+            let creator: Address = env.storage().instance().get(&DataKey::Creator).unwrap();
+            env.storage()
+                .instance()
+                .set(&DataKey::Role(Role::Owner, creator), &true);
+            env.storage().instance().set(&DataKey::OwnerCount, &1u32);
+        }
+        // Roadmap items gained escrow payout and approval fields.
+        2 => {
+            let old: Vec<RoadmapItemV2> = env
+                .storage()
+                .instance()
+                .get(&DataKey::Roadmap)
+                .unwrap_or_else(|| Vec::new(env));
+            let mut roadmap: Vec<RoadmapItem> = Vec::new(env);
+            for item in old.iter() {
+                roadmap.push_back(RoadmapItem {
+                    date: item.date,
+                    description: item.description,
+                    payout_bps: 0,
+                    approved: false,
+                    released: false,
+                });
+            }
+            env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+        }
+        // Reward tiers gained supply, claim count and availability window.
+        3 => {
+            let old: Vec<RewardTierV3> = env
+                .storage()
+                .instance()
+                .get(&DataKey::RewardTiers)
+                .unwrap_or_else(|| Vec::new(env));
+            let mut tiers: Vec<RewardTier> = Vec::new(env);
+            for tier in old.iter() {
+                tiers.push_back(RewardTier {
+                    name: tier.name,
+                    min_amount: tier.min_amount,
+                    supply: None,
+                    claimed: 0,
+                    opens_at: None,
+                    closes_at: None,
+                });
+            }
+            env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+        }
+        _ => panic!("unknown storage version"),
+    }
+}
+
+/// Pays a contribution worth `amount` goal units back to `contributor` and
+/// records the refund as claimed. In share mode the holder's shares are
+/// burned instead.
+///
+/// State is updated before the transfer so a contributor can never be paid
+/// twice, whether through `claim_refund` or `refund_batch`.
+fn pay_refund(env: &Env, token_client: &token::Client, contributor: &Address, amount: i128) {
+    if share_mode(env) {
+        burn_shares(env, contributor, amount);
+    } else {
+        checkpoint_weight(env, contributor);
+        let contribution_key = DataKey::Contribution(contributor.clone());
+        env.storage().persistent().set(&contribution_key, &0i128);
+        env.storage()
+            .persistent()
+            .extend_ttl(&contribution_key, 100, 100);
+
+        let claimed_key = DataKey::RefundClaimed(contributor.clone());
+        env.storage().persistent().set(&claimed_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&claimed_key, 100, 100);
+    }
+
+    void_receipts(env, contributor);
+
+    return_contribution(env, token_client, contributor, amount);
+
+    env.events().publish(
+        ("campaign", "refund_claimed"),
+        (contributor.clone(), amount),
+    );
+}
+
+/// Marks an escrowed milestone as approved for release.
+fn approve_milestone_item(env: &Env, index: u32) {
+    let mut roadmap: Vec<RoadmapItem> = env
+        .storage()
+        .instance()
+        .get(&DataKey::Roadmap)
+        .unwrap_or_else(|| Vec::new(env));
+    let mut item = roadmap.get(index).expect("milestone not found");
+    if item.payout_bps == 0 {
+        panic!("milestone has no payout");
+    }
+
+    item.approved = true;
+    roadmap.set(index, item);
+    env.storage().instance().set(&DataKey::Roadmap, &roadmap);
+
+    env.events()
+        .publish(("campaign", "milestone_approved"), index);
+}
+
+/// Returns a backer's current voting weight: their share balance in share
+/// mode, otherwise their contribution.
+fn voting_weight(env: &Env, backer: &Address) -> i128 {
+    if share_mode(env) {
+        return share_balance(env, backer);
+    }
+    env.storage()
+        .persistent()
+        .get(&DataKey::Contribution(backer.clone()))
+        .unwrap_or(0)
+}
+
+/// Records a backer's voting weight before it changes, once per proposal
+/// epoch, so votes on open proposals use the weight at their creation.
+fn checkpoint_weight(env: &Env, backer: &Address) {
+    let epoch: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::ProposalCount)
+        .unwrap_or(0);
+    if epoch == 0 {
+        return;
+    }
+
+    let key = DataKey::WeightCheckpoints(backer.clone());
+    let mut checkpoints: Vec<WeightCheckpoint> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| Vec::new(env));
+    if checkpoints.last().is_some_and(|last| last.epoch == epoch) {
+        return;
+    }
+
+    checkpoints.push_back(WeightCheckpoint {
+        epoch,
+        weight: voting_weight(env, backer),
+    });
+    env.storage().persistent().set(&key, &checkpoints);
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+}
+
+/// Returns a backer's voting weight when proposal `id` was created: the
+/// weight saved before their first change after it, or the current weight
+/// if it has not changed since.
+fn weight_at(env: &Env, backer: &Address, id: u32) -> i128 {
+    let checkpoints: Vec<WeightCheckpoint> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::WeightCheckpoints(backer.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    for checkpoint in checkpoints.iter() {
+        if checkpoint.epoch > id {
+            return checkpoint.weight;
+        }
+    }
+    voting_weight(env, backer)
+}
+
+/// Returns the matched funds and grants counted in the total raised.
+fn outside_funds(env: &Env) -> i128 {
+    let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+    let grants: Map<Address, i128> = env
+        .storage()
+        .instance()
+        .get(&MatchingKey::Grants)
+        .unwrap_or_else(|| Map::new(env));
+    let matched = pool.map(|pool| pool.matched).unwrap_or(0);
+    grants
+        .values()
+        .iter()
+        .fold(matched, |total, grant| total + grant)
+}
+
+/// Opens the unreleased escrow for pro-rata refunds and returns the part
+/// funded by the matching sponsor and grant funders.
+fn mark_escrow_abandoned(env: &Env) {
+    env.storage()
+        .instance()
+        .set(&DataKey::EscrowAbandoned, &true);
+
+    let base: i128 = env.storage().instance().get(&DataKey::EscrowBase).unwrap();
+    let escrow_bps: u32 = env.storage().instance().get(&DataKey::EscrowBps).unwrap();
+    let released: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::EscrowReleased)
+        .unwrap_or(0);
+    let remaining = base * escrow_bps as i128 / 10_000 - released;
+    return_outside_share(env, remaining, base);
+
+    env.events().publish(("campaign", "escrow_abandoned"), ());
+}
+
+/// Sends the matching sponsor and each grant funder their share of
+/// `remaining`, in proportion to what they put into `base`. Contributors'
+/// shares stay in the contract for them to claim.
+fn return_outside_share(env: &Env, remaining: i128, base: i128) {
+    if remaining <= 0 || base <= 0 {
+        return;
+    }
+
+    let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    let token_client = token::Client::new(env, &token_address);
+    let contract = env.current_contract_address();
+
+    let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+    if let Some(pool) = pool {
+        let amount = pool
+            .matched
+            .checked_mul(remaining)
+            .expect("refund calculation overflow")
+            / base;
+        if amount > 0 {
+            token_client.transfer(&contract, &pool.sponsor, &amount);
+            env.events()
+                .publish(("campaign", "matching_returned"), (pool.sponsor, amount));
+        }
+    }
+
+    let grants: Map<Address, i128> = env
+        .storage()
+        .instance()
+        .get(&MatchingKey::Grants)
+        .unwrap_or_else(|| Map::new(env));
+    for (funder, grant) in grants.iter() {
+        let amount = grant
+            .checked_mul(remaining)
+            .expect("refund calculation overflow")
+            / base;
+        if amount > 0 {
+            token_client.transfer(&contract, &funder, &amount);
+            env.events()
+                .publish(("campaign", "grant_returned"), (funder, amount));
+        }
+    }
+}
+
+/// Amount of the stream vested at the current time, or at the halt time if
+/// the stream was halted.
+fn vested_amount(env: &Env, stream: &VestingStream) -> i128 {
+    let now = stream.halted_at.unwrap_or(env.ledger().timestamp());
+    let elapsed = now.saturating_sub(stream.start);
+    if elapsed < stream.cliff {
+        0
+    } else if elapsed >= stream.duration {
+        stream.total
+    } else {
+        stream
+            .total
+            .checked_mul(elapsed as i128)
+            .expect("vesting calculation overflow")
+            / stream.duration as i128
+    }
+}
+
+fn halt_vesting_stream(env: &Env) {
+    let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+    let stream: Option<VestingStream> = env.storage().instance().get(&VestingKey::Vesting);
+    let mut stream = match stream {
+        Some(stream) if status == Status::Successful => stream,
+        _ => panic!("no vesting stream"),
+    };
+    if stream.halted_at.is_some() {
+        panic!("vesting halted");
+    }
+
+    stream.halted_at = Some(env.ledger().timestamp());
+    env.storage().instance().set(&VestingKey::Vesting, &stream);
+
+    // Contributors claim their part of the locked payout; matched funds and
+    // grants have no claimant, so their part goes straight back.
+    let vested = vested_amount(env, &stream);
+    return_outside_share(env, stream.total - vested, stream.base);
+    env.events().publish(
+        ("campaign", "vesting_halted"),
+        (vested, stream.total - vested),
+    );
+}
+
+/// Transfers the platform fee on `amount` to the fee recipients and returns
+/// what is left for the creator.
+///
+/// `raised` is the campaign total that selects the fee tier. The min/max caps
+/// only apply to payouts in the campaign token.
+fn deduct_platform_fee(
+    env: &Env,
+    token_client: &token::Client,
+    amount: i128,
+    raised: i128,
+) -> i128 {
+    let schedule: Option<FeeSchedule> = env.storage().instance().get(&FeeKey::PlatformConfig);
+    let Some(schedule) = schedule else {
+        return amount;
+    };
+
+    let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    let capped = token_client.address == token_address;
+    let charged: i128 = env
+        .storage()
+        .instance()
+        .get(&FeeKey::FeesCharged)
+        .unwrap_or(0);
+
+    let fee = platform_fee(&schedule, amount, raised, capped.then_some(charged));
+    if capped {
+        env.storage()
             .instance()
-            .get(&DataKey::Tags)
-            .unwrap_or(Vec::new(&env))
+            .set(&FeeKey::FeesCharged, &(charged + fee));
+    }
+
+    for share in fee_shares(env, &schedule, fee).iter() {
+        if share.amount > 0 {
+            token_client.transfer(
+                &env.current_contract_address(),
+                &share.address,
+                &share.amount,
+            );
+            env.events().publish(
+                ("campaign", "fee_transferred"),
+                (share.address, share.amount),
+            );
+        }
+    }
+
+    amount.checked_sub(fee).expect("creator payout underflow")
+}
+
+/// Rate in basis points for a campaign that raised `raised`.
+fn fee_bps_at(schedule: &FeeSchedule, raised: i128) -> u32 {
+    let mut bps = schedule.fee_bps;
+    for tier in schedule.tiers.iter() {
+        if raised >= tier.threshold {
+            bps = tier.fee_bps;
+        }
+    }
+    bps
+}
+
+/// Fee on `amount` at the rate for `raised`. With `charged`, the fees
+/// already charged in the campaign token, the result keeps the running total
+/// within the schedule's min/max caps. Never more than `amount`.
+fn platform_fee(schedule: &FeeSchedule, amount: i128, raised: i128, charged: Option<i128>) -> i128 {
+    let mut fee = amount
+        .checked_mul(fee_bps_at(schedule, raised) as i128)
+        .expect("fee calculation overflow")
+        / 10_000;
+    if let Some(charged) = charged {
+        fee = fee.max(schedule.min_fee - charged);
+        if schedule.max_fee > 0 {
+            fee = fee.min(schedule.max_fee - charged);
+        }
+    }
+    fee.clamp(0, amount.max(0))
+}
+
+/// Splits `fee` across the recipients; the last one takes the rounding dust.
+fn fee_shares(env: &Env, schedule: &FeeSchedule, fee: i128) -> Vec<FeeShare> {
+    let mut shares = Vec::new(env);
+    let mut remaining = fee;
+    let last = schedule.recipients.len().saturating_sub(1);
+    for (i, recipient) in schedule.recipients.iter().enumerate() {
+        let amount = if i as u32 == last {
+            remaining
+        } else {
+            fee.checked_mul(recipient.share_bps as i128)
+                .expect("fee calculation overflow")
+                / 10_000
+        };
+        remaining -= amount;
+        shares.push_back(FeeShare {
+            address: recipient.address,
+            amount,
+        });
+    }
+    shares
+}
+
+fn validate_fee_schedule(schedule: &FeeSchedule) {
+    if schedule.fee_bps > 10_000 || schedule.tiers.iter().any(|tier| tier.fee_bps > 10_000) {
+        panic!("platform fee cannot exceed 100%");
+    }
+    let mut previous = 0i128;
+    for tier in schedule.tiers.iter() {
+        if tier.threshold <= previous {
+            panic!("fee tiers must ascend");
+        }
+        previous = tier.threshold;
+    }
+    if schedule.min_fee < 0
+        || schedule.max_fee < 0
+        || (schedule.max_fee > 0 && schedule.min_fee > schedule.max_fee)
+    {
+        panic!("invalid fee caps");
+    }
+    let share_total: u32 = schedule
+        .recipients
+        .iter()
+        .map(|recipient| recipient.share_bps)
+        .sum();
+    if share_total != 10_000 {
+        panic!("fee shares must total 100%");
+    }
+}
+
+/// Whether `proposed` charges no more than `current` at any amount raised.
+///
+/// Rates are compared at every tier boundary of either schedule; a lower
+/// rate with lower caps can only give a lower fee.
+fn fee_schedule_within(proposed: &FeeSchedule, current: &FeeSchedule) -> bool {
+    let rates_within = core::iter::once(0)
+        .chain(proposed.tiers.iter().map(|tier| tier.threshold))
+        .chain(current.tiers.iter().map(|tier| tier.threshold))
+        .all(|raised| fee_bps_at(proposed, raised) <= fee_bps_at(current, raised));
+    let max_within =
+        current.max_fee == 0 || (proposed.max_fee > 0 && proposed.max_fee <= current.max_fee);
+    rates_within && max_within && proposed.min_fee <= current.min_fee
+}
+
+/// Drops `contributor` from `DataKey::Contributors` if present.
+fn remove_contributor(env: &Env, contributor: &Address) {
+    let mut contributors: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Contributors)
+        .unwrap();
+    if let Some(index) = contributors.first_index_of(contributor) {
+        contributors.remove(index);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Contributors, &contributors);
+        env.storage()
+            .persistent()
+            .extend_ttl(&DataKey::Contributors, 100, 100);
+    }
+}
+
+/// Sends a contribution worth `amount` goal units back to `contributor`:
+/// each secondary token in its own units and the rest in the campaign token.
+fn return_contribution(
+    env: &Env,
+    token_client: &token::Client,
+    contributor: &Address,
+    amount: i128,
+) {
+    let tokens: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AcceptedTokens)
+        .unwrap_or_else(|| Vec::new(env));
+
+    let mut primary = amount;
+    for token in tokens.iter() {
+        let balance_key = DataKey::TokenContribution(contributor.clone(), token.clone());
+        let balance: Option<TokenBalance> = env.storage().persistent().get(&balance_key);
+        let Some(balance) = balance else { continue };
+        env.storage().persistent().remove(&balance_key);
+
+        let raised_key = DataKey::TokenRaised(token.clone());
+        let raised: Option<TokenBalance> = env.storage().instance().get(&raised_key);
+        if let Some(raised) = raised {
+            let remaining = TokenBalance {
+                amount: raised.amount - balance.amount,
+                value: raised.value - balance.value,
+            };
+            env.storage().instance().set(&raised_key, &remaining);
+        }
+
+        token::Client::new(env, &token).transfer(
+            &env.current_contract_address(),
+            contributor,
+            &balance.amount,
+        );
+        primary -= balance.value;
+    }
+
+    if primary > 0 {
+        token_client.transfer(&env.current_contract_address(), contributor, &primary);
+    }
+}
+
+/// Adds `amount` raw units worth `value` goal units to a token balance.
+fn add_token_balance(
+    balance: Option<TokenBalance>,
+    amount: i128,
+    value: i128,
+) -> Result<TokenBalance, ContractError> {
+    let balance = balance.unwrap_or(TokenBalance {
+        amount: 0,
+        value: 0,
+    });
+    Ok(TokenBalance {
+        amount: balance
+            .amount
+            .checked_add(amount)
+            .ok_or(ContractError::Overflow)?,
+        value: balance
+            .value
+            .checked_add(value)
+            .ok_or(ContractError::Overflow)?,
+    })
+}
+
+/// Returns the part of `contribution` (goal units) a contributor paid in
+/// the campaign token.
+fn primary_share(env: &Env, contributor: &Address, contribution: i128) -> i128 {
+    let tokens: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AcceptedTokens)
+        .unwrap_or_else(|| Vec::new(env));
+    let mut share = contribution;
+    for token in tokens.iter() {
+        let balance: Option<TokenBalance> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenContribution(contributor.clone(), token));
+        if let Some(balance) = balance {
+            share -= balance.value;
+        }
+    }
+    share
+}
+
+/// Returns the part of `total` (goal units) raised in the campaign token.
+fn primary_raised(env: &Env, total: i128) -> i128 {
+    let tokens: Vec<Address> = env
+        .storage()
+        .instance()
+        .get(&DataKey::AcceptedTokens)
+        .unwrap_or_else(|| Vec::new(env));
+    let mut raised = total;
+    for token in tokens.iter() {
+        let balance: Option<TokenBalance> =
+            env.storage().instance().get(&DataKey::TokenRaised(token));
+        if let Some(balance) = balance {
+            raised -= balance.value;
+        }
+    }
+    raised
+}
+
+/// Transfers a campaign-token contribution in and records it. Shared by
+/// `contribute` and `contribute_with_proof` once access checks pass.
+///
+/// With `via_allowance` the tokens are pulled with `transfer_from` against
+/// the contributor's allowance instead of their signature, and a failed pull
+/// returns `PaymentFailed` rather than aborting. Such scheduled pulls neither
+/// wait out nor restart the contributor's cooldown.
+fn accept_contribution(
+    env: &Env,
+    contributor: Address,
+    amount: i128,
+    via_allowance: bool,
+) -> Result<i128, ContractError> {
+    // ── Rate limiting: enforce cooldown between contributions ──
+    let now = env.ledger().timestamp();
+    let last_time_key = DataKey::LastContributionTime(contributor.clone());
+    if !via_allowance {
+        if let Some(last_time) = env.storage().persistent().get::<_, u64>(&last_time_key) {
+            if now < last_time + CONTRIBUTION_COOLDOWN {
+                return Err(ContractError::RateLimitExceeded);
+            }
+        }
+    }
+
+    let paused: bool = env
+        .storage()
+        .instance()
+        .get(&DataKey::Paused)
+        .unwrap_or(false);
+    if paused {
+        return Err(ContractError::ContractPaused);
+    }
+
+    if !via_allowance {
+        contributor.require_auth();
+    }
+
+    let min_contribution: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::MinContribution)
+        .unwrap();
+    if amount < min_contribution {
+        panic!("amount below minimum");
+    }
+    check_contribution_limit(env, &contributor, amount)?;
+
+    let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+    if env.ledger().timestamp() > deadline {
+        return Err(ContractError::CampaignEnded);
+    }
+
+    let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+    let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
+
+    if total >= hard_cap {
+        return Err(ContractError::HardCapExceeded);
+    }
+
+    let policy: HardCapPolicy = env
+        .storage()
+        .instance()
+        .get(&DataKey::HardCapPolicy)
+        .unwrap_or(HardCapPolicy::Truncate);
+    let headroom = hard_cap - total;
+    let effective_amount = if amount <= headroom {
+        amount
+    } else {
+        match policy {
+            HardCapPolicy::Truncate => headroom,
+            HardCapPolicy::Reject => return Err(ContractError::HardCapExceeded),
+            HardCapPolicy::AcceptAndClose => amount,
+        }
+    };
+
+    let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    let token_client = token::Client::new(env, &token_address);
+
+    // Transfer tokens from the contributor to this contract.
+    if via_allowance {
+        let spender = env.current_contract_address();
+        let pulled =
+            token_client.try_transfer_from(&spender, &contributor, &spender, &effective_amount);
+        if !matches!(pulled, Ok(Ok(()))) {
+            return Err(ContractError::PaymentFailed);
+        }
+    } else {
+        token_client.transfer(
+            &contributor,
+            &env.current_contract_address(),
+            &effective_amount,
+        );
+    }
+
+    // Update the contributor's running total with overflow protection.
+    let contribution_key = DataKey::Contribution(contributor.clone());
+    let prev: i128 = env
+        .storage()
+        .persistent()
+        .get(&contribution_key)
+        .unwrap_or(0);
+
+    let new_contribution = prev
+        .checked_add(effective_amount)
+        .ok_or(ContractError::Overflow)?;
+
+    checkpoint_weight(env, &contributor);
+    env.storage()
+        .persistent()
+        .set(&contribution_key, &new_contribution);
+    env.storage()
+        .persistent()
+        .extend_ttl(&contribution_key, 100, 100);
+
+    // Update the global total raised with overflow protection.
+    let new_total = total
+        .checked_add(effective_amount)
+        .ok_or(ContractError::Overflow)?;
+
+    // Sponsor matching counts toward the goal but never past the hard cap.
+    let matched = apply_match(env, &contributor, effective_amount, hard_cap - new_total);
+    let new_total = new_total + matched;
+
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalRaised, &new_total);
+
+    if new_total >= hard_cap {
+        env.events()
+            .publish(("campaign", "hard_cap_reached"), hard_cap);
+
+        // Pull the deadline behind the current ledger: settlement checks
+        // `timestamp > deadline`, so the campaign can be settled right away
+        // and later contributions in this ledger see it as ended.
+        if policy == HardCapPolicy::AcceptAndClose {
+            env.storage()
+                .instance()
+                .set(&DataKey::Deadline, &now.saturating_sub(1));
+            env.events().publish(("campaign", "closed"), new_total);
+        }
+    }
+
+    // Track contributor address if new.
+    let mut contributors: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Contributors)
+        .unwrap();
+    if !contributors.contains(&contributor) {
+        contributors.push_back(contributor.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Contributors, &contributors);
+        env.storage()
+            .persistent()
+            .extend_ttl(&DataKey::Contributors, 100, 100);
+    }
+
+    // Emit contribution event
+    env.events()
+        .publish(("campaign", "contributed"), (contributor, effective_amount));
+
+    // Update last contribution time for rate limiting
+    if !via_allowance {
+        env.storage().persistent().set(&last_time_key, &now);
+        env.storage()
+            .persistent()
+            .extend_ttl(&last_time_key, 100, 100);
     }
 
-    /// Returns comprehensive campaign statistics.
-    pub fn get_stats(env: Env) -> CampaignStats {
-        let total_raised: i128 = env
+    Ok(effective_amount)
+}
+
+/// Sets a pending pledge to `new_amount`, keeping `TotalPledged` and
+/// `PledgeKey::Pledgers` in step, and returns the previous amount.
+fn change_pledge(env: &Env, pledger: &Address, new_amount: i128) -> Result<i128, ContractError> {
+    let status: Status = env.storage().instance().get(&DataKey::Status).unwrap();
+    if status != Status::Active {
+        panic!("campaign is not active");
+    }
+
+    let pledge_key = PledgeKey::Pledge(pledger.clone());
+    let previous: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
+    if previous <= 0 {
+        return Err(ContractError::NoPledge);
+    }
+
+    let now = env.ledger().timestamp();
+    let deadline: u64 = env.storage().instance().get(&DataKey::Deadline).unwrap();
+    if now > deadline {
+        return Err(ContractError::CampaignEnded);
+    }
+    let window: u64 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::PledgeLockWindow)
+        .unwrap_or(0);
+    if window > 0 && deadline - now <= window {
+        return Err(ContractError::PledgeLocked);
+    }
+
+    if new_amount > previous {
+        check_contribution_limit(env, pledger, new_amount - previous)?;
+        check_pledge_hard_cap(env, new_amount - previous)?;
+
+        let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+        let allowance = token::Client::new(env, &token_address)
+            .allowance(pledger, &env.current_contract_address());
+        if allowance < new_amount {
+            return Err(ContractError::InsufficientAllowance);
+        }
+    }
+
+    let total_pledged: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalPledged)
+        .unwrap_or(0);
+    let new_total_pledged = total_pledged
+        .checked_sub(previous)
+        .and_then(|total| total.checked_add(new_amount))
+        .ok_or(ContractError::Overflow)?;
+    env.storage()
+        .instance()
+        .set(&PledgeKey::TotalPledged, &new_total_pledged);
+
+    if new_amount == 0 {
+        env.storage().persistent().remove(&pledge_key);
+
+        let mut pledgers: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::Pledgers)
+            .unwrap_or_else(|| Vec::new(env));
+        if let Some(index) = pledgers.first_index_of(pledger) {
+            pledgers.remove(index);
+            env.storage()
+                .persistent()
+                .set(&PledgeKey::Pledgers, &pledgers);
+        }
+    } else {
+        env.storage().persistent().set(&pledge_key, &new_amount);
+        env.storage().persistent().extend_ttl(&pledge_key, 100, 100);
+    }
+
+    Ok(previous)
+}
+
+/// Pulls one pledge through the pledger's allowance and records it as a
+/// contribution, or marks it Defaulted if the pull fails.
+///
+/// Only the part that fits under the hard cap, given `total_raised` and the
+/// pledges collected so far, is pulled.
+fn collect_pledge(env: &Env, token_client: &token::Client, pledger: &Address, total_raised: i128) {
+    let pledge_key = PledgeKey::Pledge(pledger.clone());
+    let pledged: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
+    if pledged <= 0 {
+        return;
+    }
+
+    env.storage().persistent().set(&pledge_key, &0i128);
+    env.storage().persistent().extend_ttl(&pledge_key, 100, 100);
+
+    let collected: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalCollected)
+        .unwrap_or(0);
+    let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
+    let room = hard_cap - total_raised - collected;
+    let amount = pledged.min(room.max(0));
+    if amount == 0 {
+        record_capped_pledge(env, pledger, 0, pledged);
+        return;
+    }
+
+    let contract = env.current_contract_address();
+    let pulled = token_client.try_transfer_from(&contract, pledger, &contract, &amount);
+    if !matches!(pulled, Ok(Ok(()))) {
+        let default_key = PledgeKey::PledgeDefault(pledger.clone());
+        env.storage().persistent().set(&default_key, &amount);
+        env.storage()
+            .persistent()
+            .extend_ttl(&default_key, 100, 100);
+
+        let defaulted: i128 = env
             .storage()
             .instance()
-            .get(&DataKey::TotalRaised)
+            .get(&PledgeKey::TotalDefaulted)
             .unwrap_or(0);
-        let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
-        let contributors: Vec<Address> = env
-            .storage()
+        let new_defaulted = defaulted
+            .checked_add(amount)
+            .expect("pledge collection overflow");
+        env.storage()
             .instance()
-            .get(&DataKey::Contributors)
-            .unwrap();
+            .set(&PledgeKey::TotalDefaulted, &new_defaulted);
 
-        let progress_bps = if goal > 0 {
-            let raw = (total_raised * 10_000) / goal;
-            if raw > 10_000 {
-                10_000
-            } else {
-                raw as u32
+        env.events()
+            .publish(("campaign", "pledge_defaulted"), (pledger.clone(), amount));
+        return;
+    }
+
+    let contribution_key = DataKey::Contribution(pledger.clone());
+    let prev: i128 = env
+        .storage()
+        .persistent()
+        .get(&contribution_key)
+        .unwrap_or(0);
+    let new_contribution = prev
+        .checked_add(amount)
+        .expect("pledge collection overflow");
+    checkpoint_weight(env, pledger);
+    env.storage()
+        .persistent()
+        .set(&contribution_key, &new_contribution);
+    env.storage()
+        .persistent()
+        .extend_ttl(&contribution_key, 100, 100);
+    env.storage()
+        .instance()
+        .set(&PledgeKey::TotalCollected, &(collected + amount));
+
+    let mut contributors: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Contributors)
+        .unwrap();
+    if !contributors.contains(pledger) {
+        contributors.push_back(pledger.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Contributors, &contributors);
+        env.storage()
+            .persistent()
+            .extend_ttl(&DataKey::Contributors, 100, 100);
+    }
+
+    mint_shares(env, pledger, amount);
+    mint_receipt(env, pledger, amount);
+
+    if amount < pledged {
+        record_capped_pledge(env, pledger, amount, pledged - amount);
+    }
+}
+
+/// Records that the hard cap left `uncollected` of a pledge with `pledger`.
+fn record_capped_pledge(env: &Env, pledger: &Address, collected: i128, uncollected: i128) {
+    let key = PledgeKey::PledgeCapped(pledger.clone());
+    env.storage().persistent().set(
+        &key,
+        &CappedPledge {
+            collected,
+            uncollected,
+        },
+    );
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+
+    env.events().publish(
+        ("campaign", "pledge_capped"),
+        (pledger.clone(), collected, uncollected),
+    );
+}
+
+/// Folds the collected pledges into `TotalRaised` and re-checks the goal,
+/// since defaults may have left the campaign short.
+fn finish_pledge_collection(env: &Env, total_raised: i128) {
+    let defaulted: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalDefaulted)
+        .unwrap_or(0);
+    let collected: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalCollected)
+        .unwrap_or(0);
+    let new_total = total_raised + collected;
+
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalRaised, &new_total);
+    env.storage()
+        .instance()
+        .set(&PledgeKey::TotalPledged, &0i128);
+    env.storage().instance().remove(&PledgeKey::TotalCollected);
+
+    env.events()
+        .publish(("campaign", "pledges_collected"), collected);
+
+    let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+    if new_total < goal {
+        env.events().publish(
+            ("campaign", "goal_missed_after_defaults"),
+            (new_total, defaulted),
+        );
+    }
+}
+
+/// Returns whether `total_raised` plus outstanding pledges reaches the goal.
+///
+/// Pledges count at face value, so this stays constant-cost. A pledge whose
+/// allowance was revoked is only discounted once collection fails to pull
+/// it and records the default; once a batch has started the check has
+/// already passed.
+fn pledges_cover_goal(env: &Env, total_raised: i128) -> bool {
+    if env.storage().instance().has(&PledgeKey::PledgeCursor) {
+        return true;
+    }
+    let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+    let total_pledged: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalPledged)
+        .unwrap_or(0);
+    total_raised + total_pledged >= goal
+}
+
+/// Fails with `HardCapExceeded` if pledging `amount` more would take
+/// contributions plus pending pledges past the hard cap.
+fn check_pledge_hard_cap(env: &Env, amount: i128) -> Result<(), ContractError> {
+    let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+    let pledged: i128 = env
+        .storage()
+        .instance()
+        .get(&PledgeKey::TotalPledged)
+        .unwrap_or(0);
+    let hard_cap: i128 = env.storage().instance().get(&DataKey::HardCap).unwrap();
+    let new_total = total
+        .checked_add(pledged)
+        .and_then(|total| total.checked_add(amount))
+        .ok_or(ContractError::Overflow)?;
+    if new_total > hard_cap {
+        return Err(ContractError::HardCapExceeded);
+    }
+    Ok(())
+}
+
+/// Returns the lower of the configured absolute and goal-based limits, or
+/// None if neither is set.
+fn effective_contribution_limit(env: &Env) -> Option<i128> {
+    let limit: Option<ContributionLimit> =
+        env.storage().instance().get(&DataKey::ContributionLimit);
+    let limit = limit?;
+
+    let goal: i128 = env.storage().instance().get(&DataKey::Goal).unwrap();
+    let by_goal = goal
+        .checked_mul(limit.max_goal_bps as i128)
+        .expect("limit calculation overflow")
+        / 10_000;
+
+    match (limit.max_amount > 0, limit.max_goal_bps > 0) {
+        (true, true) => Some(limit.max_amount.min(by_goal)),
+        (true, false) => Some(limit.max_amount),
+        (false, true) => Some(by_goal),
+        (false, false) => None,
+    }
+}
+
+/// Fails with `ContributionLimitExceeded` if adding `amount` would take the
+/// address's contributions plus pledges past the per-address limit.
+fn check_contribution_limit(
+    env: &Env,
+    contributor: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let Some(max) = effective_contribution_limit(env) else {
+        return Ok(());
+    };
+
+    let contributed: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Contribution(contributor.clone()))
+        .unwrap_or(0);
+    let pledged: i128 = env
+        .storage()
+        .persistent()
+        .get(&PledgeKey::Pledge(contributor.clone()))
+        .unwrap_or(0);
+    let combined = contributed
+        .checked_add(pledged)
+        .and_then(|sum| sum.checked_add(amount))
+        .ok_or(ContractError::Overflow)?;
+    if combined > max {
+        return Err(ContractError::ContributionLimitExceeded);
+    }
+    Ok(())
+}
+
+/// Frees the reward tier slot held by `contributor`, if any.
+fn release_tier(env: &Env, contributor: &Address) {
+    let selected_key = DataKey::SelectedTier(contributor.clone());
+    let Some(tier_id) = env.storage().persistent().get::<_, u32>(&selected_key) else {
+        return;
+    };
+    env.storage().persistent().remove(&selected_key);
+
+    let mut tiers: Vec<RewardTier> = env
+        .storage()
+        .instance()
+        .get(&DataKey::RewardTiers)
+        .unwrap_or_else(|| Vec::new(env));
+    if let Some(mut tier) = tiers.get(tier_id) {
+        tier.claimed -= 1;
+        tiers.set(tier_id, tier);
+        env.storage().instance().set(&DataKey::RewardTiers, &tiers);
+    }
+
+    env.events().publish(
+        ("campaign", "tier_released"),
+        (contributor.clone(), tier_id),
+    );
+}
+
+/// Frees `contributor`'s reserved tier once `remaining` no longer meets its
+/// minimum.
+fn release_tier_if_short(env: &Env, contributor: &Address, remaining: i128) {
+    let selected: Option<u32> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::SelectedTier(contributor.clone()));
+    let Some(tier_id) = selected else {
+        return;
+    };
+
+    let tiers: Vec<RewardTier> = env
+        .storage()
+        .instance()
+        .get(&DataKey::RewardTiers)
+        .unwrap_or_else(|| Vec::new(env));
+    let covered = tiers
+        .get(tier_id)
+        .is_some_and(|tier| remaining >= tier.min_amount);
+    if !covered {
+        release_tier(env, contributor);
+    }
+}
+
+/// Returns the index of the reward tier `user` is entitled to: the tier
+/// reserved with `contribute_for_tier`, or else the highest unlimited tier
+/// the contribution qualifies for. In share mode the share balance stands
+/// in for the contribution, so tiers follow transferred shares.
+fn user_tier_index(env: &Env, user: &Address) -> Option<u32> {
+    let selected: Option<u32> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::SelectedTier(user.clone()));
+    if selected.is_some() {
+        return selected;
+    }
+
+    let contribution: i128 = if share_mode(env) {
+        share_balance(env, user)
+    } else {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Contribution(user.clone()))
+            .unwrap_or(0)
+    };
+    if contribution <= 0 {
+        return None;
+    }
+
+    let tiers: Vec<RewardTier> = env
+        .storage()
+        .instance()
+        .get(&DataKey::RewardTiers)
+        .unwrap_or_else(|| Vec::new(env));
+
+    let mut best: Option<(u32, i128)> = None;
+    for (index, tier) in tiers.iter().enumerate() {
+        if tier.supply.is_none() && contribution >= tier.min_amount {
+            let is_better = match best {
+                None => true,
+                Some((_, min_amount)) => tier.min_amount > min_amount,
+            };
+            if is_better {
+                best = Some((index as u32, tier.min_amount));
             }
-        } else {
-            0
-        };
+        }
+    }
 
-        let contributor_count = contributors.len();
-        let (average_contribution, largest_contribution) = if contributor_count == 0 {
-            (0, 0)
-        } else {
-            let average = total_raised / contributor_count as i128;
-            let mut largest = 0i128;
-            for contributor in contributors.iter() {
-                let amount: i128 = env
-                    .storage()
-                    .instance()
-                    .get(&DataKey::Contribution(contributor))
-                    .unwrap_or(0);
-                if amount > largest {
-                    largest = amount;
-                }
+    best.map(|(index, _)| index)
+}
+
+/// Mints a receipt for a contribution of `amount` goal units to
+/// `contributor`.
+fn mint_receipt(env: &Env, contributor: &Address, amount: i128) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&ReceiptKey::ReceiptCount)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&ReceiptKey::ReceiptCount, &(id + 1));
+
+    let receipt = Receipt {
+        id,
+        owner: contributor.clone(),
+        campaign: env.current_contract_address(),
+        amount,
+        tier: user_tier_index(env, contributor),
+        timestamp: env.ledger().timestamp(),
+        void: false,
+    };
+    let receipt_key = ReceiptKey::Receipt(id);
+    env.storage().persistent().set(&receipt_key, &receipt);
+    env.storage()
+        .persistent()
+        .extend_ttl(&receipt_key, 100, 100);
+
+    for key in [
+        ReceiptKey::ReceiptsOf(contributor.clone()),
+        ReceiptKey::ReceiptsMinted(contributor.clone()),
+    ] {
+        let mut ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| Vec::new(env));
+        ids.push_back(id);
+        set_receipt_list(env, key, &ids);
+    }
+
+    env.events().publish(
+        ("campaign", "receipt_minted"),
+        (contributor.clone(), id, amount),
+    );
+
+    id
+}
+
+/// Marks every receipt minted for `contributor` as void, wherever it is now
+/// held.
+fn void_receipts(env: &Env, contributor: &Address) {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&ReceiptKey::ReceiptsMinted(contributor.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    for id in ids.iter() {
+        let receipt_key = ReceiptKey::Receipt(id);
+        let receipt: Option<Receipt> = env.storage().persistent().get(&receipt_key);
+        if let Some(mut receipt) = receipt {
+            if receipt.void {
+                continue;
             }
-            (average, largest)
-        };
+            receipt.void = true;
+            env.storage().persistent().set(&receipt_key, &receipt);
+            env.events().publish(("campaign", "receipt_voided"), id);
+        }
+    }
+}
 
-        CampaignStats {
-            total_raised,
-            goal,
-            progress_bps,
-            contributor_count,
-            average_contribution,
-            largest_contribution,
+/// Takes `amount` goal units off the receipts minted for `contributor`,
+/// newest first, voiding any receipt that drops to zero.
+fn shrink_receipts(env: &Env, contributor: &Address, amount: i128) {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&ReceiptKey::ReceiptsMinted(contributor.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    let mut left = amount;
+    for id in ids.iter().rev() {
+        if left <= 0 {
+            break;
+        }
+        let receipt_key = ReceiptKey::Receipt(id);
+        let receipt: Option<Receipt> = env.storage().persistent().get(&receipt_key);
+        let Some(mut receipt) = receipt else {
+            continue;
+        };
+        if receipt.void {
+            continue;
+        }
+        let taken = left.min(receipt.amount);
+        left -= taken;
+        receipt.amount -= taken;
+        if receipt.amount == 0 {
+            receipt.void = true;
+            env.events().publish(("campaign", "receipt_voided"), id);
+        } else {
+            env.events()
+                .publish(("campaign", "receipt_reduced"), (id, receipt.amount));
         }
+        env.storage().persistent().set(&receipt_key, &receipt);
     }
+}
 
-    /// Returns the campaign title.
-    pub fn title(env: Env) -> String {
-        let empty = String::from_str(&env, "");
-        env.storage()
-            .instance()
-            .get(&DataKey::Title)
-            .unwrap_or(empty)
+/// Stores a list of receipt ids under `key`.
+fn set_receipt_list(env: &Env, key: ReceiptKey, ids: &Vec<u64>) {
+    env.storage().persistent().set(&key, ids);
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+}
+
+/// Returns whether the campaign is in share mode.
+fn share_mode(env: &Env) -> bool {
+    env.storage().instance().has(&ShareKey::Metadata)
+}
+
+/// Panics unless the campaign is in share mode.
+fn require_share_mode(env: &Env) {
+    if !share_mode(env) {
+        panic!("share token not enabled");
     }
+}
 
-    /// Returns the campaign description.
-    pub fn description(env: Env) -> String {
-        let empty = String::from_str(&env, "");
+/// Returns the share token metadata, panicking outside share mode.
+fn share_token_info(env: &Env) -> ShareTokenInfo {
+    env.storage()
+        .instance()
+        .get(&ShareKey::Metadata)
+        .expect("share token not enabled")
+}
+
+/// Returns every address that has held shares.
+fn share_holders(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&ShareKey::Holders)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Returns the share balance of `id`.
+fn share_balance(env: &Env, id: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&ShareKey::Balance(id.clone()))
+        .unwrap_or(0)
+}
+
+/// Stores the share balance of `id`, listing it in `ShareKey::Holders` the
+/// first time it holds shares.
+fn set_share_balance(env: &Env, id: &Address, amount: i128) {
+    checkpoint_weight(env, id);
+    let key = ShareKey::Balance(id.clone());
+    if amount > 0 && !env.storage().persistent().has(&key) {
+        let mut holders = share_holders(env);
+        holders.push_back(id.clone());
+        env.storage().persistent().set(&ShareKey::Holders, &holders);
         env.storage()
-            .instance()
-            .get(&DataKey::Description)
-            .unwrap_or(empty)
+            .persistent()
+            .extend_ttl(&ShareKey::Holders, 100, 100);
     }
+    env.storage().persistent().set(&key, &amount);
+    env.storage().persistent().extend_ttl(&key, 100, 100);
+}
 
-    /// Returns the campaign social links.
-    pub fn socials(env: Env) -> String {
-        let empty = String::from_str(&env, "");
+/// Returns the unexpired allowance `from` granted `spender`.
+fn share_allowance(env: &Env, from: &Address, spender: &Address) -> i128 {
+    let allowance: Option<ShareAllowance> = env
+        .storage()
+        .temporary()
+        .get(&ShareKey::Allowance(from.clone(), spender.clone()));
+    match allowance {
+        Some(a) if a.expiration_ledger >= env.ledger().sequence() => a.amount,
+        _ => 0,
+    }
+}
+
+/// Deducts `amount` from the allowance `from` granted `spender`.
+fn spend_share_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+    let allowance = share_allowance(env, from, spender);
+    if allowance < amount {
+        panic!("insufficient allowance");
+    }
+    if amount > 0 {
+        let key = ShareKey::Allowance(from.clone(), spender.clone());
+        let mut stored: ShareAllowance = env.storage().temporary().get(&key).unwrap();
+        stored.amount = allowance - amount;
+        env.storage().temporary().set(&key, &stored);
+    }
+}
+
+/// Moves `amount` shares from `from` to `to`.
+fn move_shares(env: &Env, from: &Address, to: &Address, amount: i128) {
+    if amount < 0 {
+        panic!("negative amount");
+    }
+    let from_balance = share_balance(env, from);
+    if from_balance < amount {
+        panic!("insufficient balance");
+    }
+    set_share_balance(env, from, from_balance - amount);
+    set_share_balance(env, to, share_balance(env, to) + amount);
+
+    env.events()
+        .publish(("transfer", from.clone(), to.clone()), amount);
+}
+
+/// Mints `amount` shares to `to` if the campaign is in share mode.
+fn mint_shares(env: &Env, to: &Address, amount: i128) {
+    if !share_mode(env) || amount <= 0 {
+        return;
+    }
+    set_share_balance(env, to, share_balance(env, to) + amount);
+    let supply: i128 = env.storage().instance().get(&ShareKey::Supply).unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&ShareKey::Supply, &(supply + amount));
+
+    env.events().publish(("mint", to.clone()), amount);
+}
+
+/// Burns `amount` shares held by `from`.
+fn burn_shares(env: &Env, from: &Address, amount: i128) {
+    if amount < 0 {
+        panic!("negative amount");
+    }
+    let balance = share_balance(env, from);
+    if balance < amount {
+        panic!("insufficient balance");
+    }
+    set_share_balance(env, from, balance - amount);
+    let supply: i128 = env.storage().instance().get(&ShareKey::Supply).unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&ShareKey::Supply, &(supply - amount));
+
+    env.events().publish(("burn", from.clone()), amount);
+}
+
+/// Returns what `holder` can reclaim from a failed campaign: the share
+/// balance in share mode, otherwise the unclaimed contribution.
+fn refund_entitlement(env: &Env, holder: &Address) -> i128 {
+    if share_mode(env) {
+        return share_balance(env, holder);
+    }
+    if env
+        .storage()
+        .persistent()
+        .has(&DataKey::RefundClaimed(holder.clone()))
+    {
+        return 0;
+    }
+    env.storage()
+        .persistent()
+        .get(&DataKey::Contribution(holder.clone()))
+        .unwrap_or(0)
+}
+
+/// Pays each referrer the referral bonus on their attributed contributions,
+/// never more than `available` in total. Returns the amount paid.
+fn pay_referral_bonuses(env: &Env, token_client: &token::Client, available: i128) -> i128 {
+    let bps: u32 = env
+        .storage()
+        .instance()
+        .get(&ReferralKey::ReferralBps)
+        .unwrap_or(0);
+    if bps == 0 {
+        return 0;
+    }
+
+    let referrers: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&ReferralKey::Referrers)
+        .unwrap_or_else(|| Vec::new(env));
+
+    let mut paid = 0i128;
+    for referrer in referrers.iter() {
+        let referred: i128 = env
+            .storage()
+            .persistent()
+            .get(&ReferralKey::ReferredTotal(referrer.clone()))
+            .unwrap_or(0);
+        let bonus = (referred
+            .checked_mul(bps as i128)
+            .expect("referral calculation overflow")
+            / 10_000)
+            .min(available - paid);
+        if bonus <= 0 {
+            continue;
+        }
+
+        let earnings_key = ReferralKey::ReferralEarnings(referrer.clone());
+        let earned: i128 = env.storage().persistent().get(&earnings_key).unwrap_or(0);
         env.storage()
-            .instance()
-            .get(&DataKey::SocialLinks)
-            .unwrap_or(empty)
+            .persistent()
+            .set(&earnings_key, &(earned + bonus));
+        env.storage()
+            .persistent()
+            .extend_ttl(&earnings_key, 100, 100);
+
+        token_client.transfer(&env.current_contract_address(), &referrer, &bonus);
+        env.events()
+            .publish(("campaign", "referral_paid"), (referrer, bonus));
+        paid += bonus;
     }
 
-    /// Returns the contract version.
-    ///
-    /// This view function allows external tools to detect which version of the
-    /// contract logic is currently running at this address. The version must be
-    /// manually incremented with every contract upgrade (see Issue #38).
-    pub fn version(_env: Env) -> u32 {
-        CONTRACT_VERSION
+    paid
+}
+
+/// Removes up to `amount` of `contributor`'s referred contribution from
+/// their referrer's total after the contributor takes funds back out.
+fn unrefer(env: &Env, contributor: &Address, amount: i128) {
+    let referral_key = ReferralKey::Referral(contributor.clone());
+    let referral: Option<ReferralRecord> = env.storage().persistent().get(&referral_key);
+    let Some(mut referral) = referral else {
+        return;
+    };
+
+    let removed = amount.min(referral.amount);
+    referral.amount -= removed;
+    env.storage().persistent().set(&referral_key, &referral);
+
+    let total_key = ReferralKey::ReferredTotal(referral.referrer.clone());
+    let referred: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&total_key, &(referred - removed));
+    rank_referrer(env, &referral.referrer, referred - removed);
+}
+
+/// Returns the referral leaderboard, highest total first.
+fn leaderboard(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&ReferralKey::Leaderboard)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Moves `referrer` to its place on the leaderboard for a new total of
+/// `referred`, dropping whoever falls past `LEADERBOARD_SIZE`.
+fn rank_referrer(env: &Env, referrer: &Address, referred: i128) {
+    let mut board = leaderboard(env);
+    if let Some(index) = board.first_index_of(referrer) {
+        board.remove(index);
+    }
+    if referred > 0 {
+        let mut index = board.len();
+        while index > 0 {
+            let above: i128 = env
+                .storage()
+                .persistent()
+                .get(&ReferralKey::ReferredTotal(board.get(index - 1).unwrap()))
+                .unwrap_or(0);
+            if above >= referred {
+                break;
+            }
+            index -= 1;
+        }
+        if index < LEADERBOARD_SIZE {
+            board.insert(index, referrer.clone());
+        }
+        if board.len() > LEADERBOARD_SIZE {
+            board.pop_back();
+        }
     }
+    env.storage()
+        .persistent()
+        .set(&ReferralKey::Leaderboard, &board);
+    env.storage()
+        .persistent()
+        .extend_ttl(&ReferralKey::Leaderboard, 100, 100);
+}
 
-    /// Returns the token contract address used for contributions.
-    pub fn token(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::Token).unwrap()
+/// Matches a contribution of `amount` from the sponsor pool, limited by the
+/// pool balance, the per-backer cap and `headroom`. Returns the match.
+fn apply_match(env: &Env, contributor: &Address, amount: i128, headroom: i128) -> i128 {
+    let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+    let Some(mut pool) = pool else {
+        return 0;
+    };
+
+    let matched_key = MatchingKey::Matched(contributor.clone());
+    let already: i128 = env.storage().persistent().get(&matched_key).unwrap_or(0);
+
+    let mut matched = amount
+        .checked_mul(pool.ratio_bps as i128)
+        .expect("match calculation overflow")
+        / 10_000;
+    matched = matched.min(pool.deposited - pool.matched).min(headroom);
+    if pool.per_backer_cap > 0 {
+        matched = matched.min(pool.per_backer_cap - already);
+    }
+    if matched <= 0 {
+        return 0;
+    }
+
+    pool.matched += matched;
+    env.storage()
+        .instance()
+        .set(&MatchingKey::MatchingPool, &pool);
+    env.storage()
+        .persistent()
+        .set(&matched_key, &(already + matched));
+    env.storage()
+        .persistent()
+        .extend_ttl(&matched_key, 100, 100);
+
+    env.events()
+        .publish(("campaign", "matched"), (contributor.clone(), matched));
+
+    matched
+}
+
+/// Releases the match behind `amount` of `contributor`'s contribution back
+/// into the pool after the contributor takes funds out.
+fn unmatch(env: &Env, contributor: &Address, amount: i128) {
+    let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+    let Some(mut pool) = pool else {
+        return;
+    };
+
+    let matched_key = MatchingKey::Matched(contributor.clone());
+    let already: i128 = env.storage().persistent().get(&matched_key).unwrap_or(0);
+    let released = (amount
+        .checked_mul(pool.ratio_bps as i128)
+        .expect("match calculation overflow")
+        / 10_000)
+        .min(already);
+    if released <= 0 {
+        return;
+    }
+
+    pool.matched -= released;
+    env.storage()
+        .instance()
+        .set(&MatchingKey::MatchingPool, &pool);
+    env.storage()
+        .persistent()
+        .set(&matched_key, &(already - released));
+
+    let total: i128 = env.storage().instance().get(&DataKey::TotalRaised).unwrap();
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalRaised, &(total - released));
+
+    env.events()
+        .publish(("campaign", "unmatched"), (contributor.clone(), released));
+}
+
+/// Sends the matched part of the pool back to the sponsor after the
+/// campaign failed.
+fn return_matched_funds(env: &Env) {
+    let pool: Option<MatchingPool> = env.storage().instance().get(&MatchingKey::MatchingPool);
+    let Some(mut pool) = pool else {
+        return;
+    };
+    if pool.returned || pool.matched == 0 {
+        return;
+    }
+
+    pool.returned = true;
+    env.storage()
+        .instance()
+        .set(&MatchingKey::MatchingPool, &pool);
+
+    let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    token::Client::new(env, &token_address).transfer(
+        &env.current_contract_address(),
+        &pool.sponsor,
+        &pool.matched,
+    );
+
+    env.events().publish(
+        ("campaign", "matching_returned"),
+        (pool.sponsor, pool.matched),
+    );
+}
+
+/// Sends every outside grant back to its funder once the campaign fails.
+fn return_grants(env: &Env) {
+    let grants: Option<Map<Address, i128>> = env.storage().instance().get(&MatchingKey::Grants);
+    let Some(grants) = grants else {
+        return;
+    };
+    env.storage().instance().remove(&MatchingKey::Grants);
+
+    let token_address: Address = env.storage().instance().get(&DataKey::Token).unwrap();
+    let token_client = token::Client::new(env, &token_address);
+    for (funder, amount) in grants.iter() {
+        token_client.transfer(&env.current_contract_address(), &funder, &amount);
+        env.events()
+            .publish(("campaign", "grant_returned"), (funder, amount));
     }
 }
@@ -1,20 +1,29 @@
 // Factory contract for batch campaign initialization
 // Implements Issue #68 and extends Issue #23
 
-use soroban_sdk::{contractimpl, contracttype, BytesN, Address, Env, Symbol, String, Vec};
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, IntoVal, String,
+    Symbol, Vec,
+};
+
+mod round;
+pub use round::{MatchAllocation, Round, RoundError, RoundTally};
 
 // Registry key for storing deployed campaigns
 const REGISTRY_KEY: &str = "campaign_registry";
 
-// The WASM hash for the crowdfund contract (should be set to the correct value in production)
-const CROWDFUND_WASM_HASH: [u8; 32] = [0u8; 32]; // TODO: Replace with actual hash
+// Storage key for the uploaded crowdfund WASM hash, set at deployment
+const CAMPAIGN_WASM_KEY: &str = "campaign_wasm";
 
 #[contracttype]
 pub struct BatchCreatedEvent {
     pub count: u32,
     pub addresses: Vec<Address>,
 }
-#[derive(Clone)]
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CampaignConfig {
     pub creator: Address,
     pub token: Address,
@@ -24,18 +33,35 @@ pub struct CampaignConfig {
     pub description: String,
 }
 
-#[derive(Clone)]
+#[contract]
 pub struct FactoryContract;
 
-#[derive(Debug, PartialEq)]
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum ContractError {
-    EmptyBatch,
-    InvalidConfig { index: usize },
-    // ...other errors
+    EmptyBatch = 1,
+    InvalidConfig = 2,
 }
 
 #[contractimpl]
 impl FactoryContract {
+    /// Deploy the factory with the hash of the uploaded crowdfund WASM that
+    /// its campaigns run.
+    pub fn __constructor(env: Env, campaign_wasm_hash: BytesN<32>) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, CAMPAIGN_WASM_KEY), &campaign_wasm_hash);
+    }
+
+    /// Returns the crowdfund WASM hash new campaigns are deployed from.
+    pub fn campaign_wasm_hash(env: Env) -> BytesN<32> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, CAMPAIGN_WASM_KEY))
+            .unwrap()
+    }
+
     pub fn create_campaigns_batch(
         env: Env,
         configs: Vec<CampaignConfig>,
@@ -45,51 +71,53 @@ impl FactoryContract {
         }
         let mut deployed = Vec::new(&env);
         // Validate all configs first
-        for (i, config) in configs.iter().enumerate() {
+        for config in configs.iter() {
             if config.goal <= 0 || config.title.is_empty() || config.description.is_empty() {
-                return Err(ContractError::InvalidConfig { index: i });
+                return Err(ContractError::InvalidConfig);
             }
         }
-        // Deploy and initialize all campaigns
-        for config in configs.iter() {
-            let campaign_addr = deploy_and_init_campaign(&env, config);
-            deployed.push_back(campaign_addr);
-        }
-        // Store all deployed addresses in the factory registry
+        let registry_key = Symbol::new(&env, REGISTRY_KEY);
         let mut registry: Vec<Address> = env
             .storage()
             .persistent()
-            .get(&REGISTRY_KEY.into())
+            .get(&registry_key)
             .unwrap_or(Vec::new(&env));
-        for addr in deployed.iter() {
-            registry.push_back(addr.clone());
+        // Deploy and initialize all campaigns
+        for config in configs.iter() {
+            let campaign_addr = deploy_and_init_campaign(&env, &config, registry.len());
+            registry.push_back(campaign_addr.clone());
+            deployed.push_back(campaign_addr);
         }
-        env.storage().persistent().set(&REGISTRY_KEY.into(), &registry);
+        // Store all deployed addresses in the factory registry
+        env.storage().persistent().set(&registry_key, &registry);
         // Emit batch_campaigns_created event
         let event = BatchCreatedEvent {
-            count: deployed.len() as u32,
+            count: deployed.len(),
             addresses: deployed.clone(),
         };
-        env.events().publish(("factory", "batch_campaigns_created"), event);
+        env.events()
+            .publish(("factory", "batch_campaigns_created"), event);
         Ok(deployed)
     }
 }
 
-fn deploy_and_init_campaign(env: &Env, config: &CampaignConfig) -> Address {
-    // Deploy the crowdfund contract
-    let wasm_hash = BytesN::from_array(env, &CROWDFUND_WASM_HASH);
+fn deploy_and_init_campaign(env: &Env, config: &CampaignConfig, index: u32) -> Address {
+    // Deploy the crowdfund contract, salted by its position in the registry
+    let wasm_hash = FactoryContract::campaign_wasm_hash(env.clone());
+    let mut salt = [0u8; 32];
+    salt[28..].copy_from_slice(&index.to_be_bytes());
     let campaign_addr = env
         .deployer()
-        .with_current_contract(env.current_contract_address())
-        .deploy_contract(wasm_hash);
+        .with_current_contract(BytesN::from_array(env, &salt))
+        .deploy_v2(wasm_hash, ());
     // Call initialize on the deployed contract
     // NOTE: Hard cap, min_contribution, platform_config are set to defaults for this example
     let hard_cap = config.goal;
     let min_contribution = 1i128;
     let platform_config: Option<()> = None;
-    env.invoke_contract(
+    env.invoke_contract::<()>(
         &campaign_addr,
-        &Symbol::short("initialize"),
+        &Symbol::new(env, "initialize"),
         (
             config.creator.clone(),
             config.token.clone(),
@@ -98,90 +126,110 @@ fn deploy_and_init_campaign(env: &Env, config: &CampaignConfig) -> Address {
             config.deadline,
             min_contribution,
             platform_config,
-        ),
+        )
+            .into_val(env),
     );
     campaign_addr
 }
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Address, Env, Vec};
+    use soroban_sdk::{testutils::Address as _, Address, Env, String, Vec};
+
+    /// A minimal stand-in for the crowdfund WASM: a module targeting
+    /// protocol 22 whose only export is a seven-argument `initialize` that
+    /// returns void.
+    #[rustfmt::skip]
+    const STAND_IN_CAMPAIGN_WASM: &[u8] = &[
+        // header
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        // type 0: (i64 x 7) -> i64
+        0x01, 0x0c, 0x01, 0x60, 0x07, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x01, 0x7e,
+        // function 0 has type 0
+        0x03, 0x02, 0x01, 0x00,
+        // export function 0 as "initialize"
+        0x07, 0x0e, 0x01, 0x0a,
+        b'i', b'n', b'i', b't', b'i', b'a', b'l', b'i', b'z', b'e', 0x00, 0x00,
+        // body: return void
+        0x0a, 0x06, 0x01, 0x04, 0x00, 0x42, 0x02, 0x0b,
+        // custom "contractenvmetav0": interface version, protocol 22
+        0x00, 0x1e, 0x11,
+        b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a',
+        b'v', b'0',
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn setup(env: &Env) -> FactoryContractClient<'_> {
+        env.mock_all_auths();
+        let wasm_hash = env.deployer().upload_contract_wasm(STAND_IN_CAMPAIGN_WASM);
+        let factory = env.register(FactoryContract, (wasm_hash,));
+        FactoryContractClient::new(env, &factory)
+    }
+
+    fn config(env: &Env, goal: i128, deadline: u64, title: &str) -> CampaignConfig {
+        CampaignConfig {
+            creator: Address::generate(env),
+            token: Address::generate(env),
+            goal,
+            deadline,
+            title: String::from_str(env, title),
+            description: String::from_str(env, title),
+        }
+    }
 
     #[test]
     fn test_batch_deploys_campaigns() {
         let env = Env::default();
+        let client = setup(&env);
         let configs = Vec::from_array(
             &env,
             [
-                CampaignConfig {
-                    creator: Address::random(&env),
-                    token: Address::random(&env),
-                    goal: 1000,
-                    deadline: 123456,
-                    title: "Campaign 1".to_string(),
-                    description: "Desc 1".to_string(),
-                },
-                CampaignConfig {
-                    creator: Address::random(&env),
-                    token: Address::random(&env),
-                    goal: 2000,
-                    deadline: 223456,
-                    title: "Campaign 2".to_string(),
-                    description: "Desc 2".to_string(),
-                },
-                CampaignConfig {
-                    creator: Address::random(&env),
-                    token: Address::random(&env),
-                    goal: 3000,
-                    deadline: 323456,
-                    title: "Campaign 3".to_string(),
-                    description: "Desc 3".to_string(),
-                },
+                config(&env, 1000, 123456, "Campaign 1"),
+                config(&env, 2000, 223456, "Campaign 2"),
+                config(&env, 3000, 323456, "Campaign 3"),
             ],
         );
-        let result = FactoryContract::create_campaigns_batch(env.clone(), configs.clone());
-        assert!(result.is_ok());
-        let deployed = result.unwrap();
+        let deployed = client.create_campaigns_batch(&configs);
         assert_eq!(deployed.len(), 3);
-        // TODO: Check registry and returned addresses
+        let registry: Vec<Address> = env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get(&Symbol::new(&env, REGISTRY_KEY))
+                .unwrap()
+        });
+        assert_eq!(registry, deployed);
+
+        // Later batches deploy to fresh addresses after the registry.
+        let more = client.create_campaigns_batch(&Vec::from_array(
+            &env,
+            [config(&env, 4000, 423456, "Campaign 4")],
+        ));
+        assert!(!deployed.contains(more.get(0).unwrap()));
     }
 
     #[test]
     fn test_empty_batch_rejected() {
         let env = Env::default();
+        let client = setup(&env);
         let configs = Vec::new(&env);
-        let result = FactoryContract::create_campaigns_batch(env, configs);
-        assert_eq!(result, Err(ContractError::EmptyBatch));
+        let result = client.try_create_campaigns_batch(&configs);
+        assert_eq!(result, Err(Ok(ContractError::EmptyBatch)));
     }
 
     #[test]
     fn test_invalid_config_rolls_back_batch() {
         let env = Env::default();
+        let client = setup(&env);
         let configs = Vec::from_array(
             &env,
             [
-                CampaignConfig {
-                    creator: Address::random(&env),
-                    token: Address::random(&env),
-                    goal: 1000,
-                    deadline: 123456,
-                    title: "Valid".to_string(),
-                    description: "Valid".to_string(),
-                },
-                CampaignConfig {
-                    creator: Address::random(&env),
-                    token: Address::random(&env),
-                    goal: -1, // Invalid goal
-                    deadline: 223456,
-                    title: "Invalid".to_string(),
-                    description: "Invalid".to_string(),
-                },
+                config(&env, 1000, 123456, "Valid"),
+                config(&env, -1, 223456, "Invalid"), // Invalid goal
             ],
         );
-        let result = FactoryContract::create_campaigns_batch(env, configs);
-        assert_eq!(result, Err(ContractError::InvalidConfig { index: 1 }));
+        let result = client.try_create_campaigns_batch(&configs);
+        assert_eq!(result, Err(Ok(ContractError::InvalidConfig)));
     }
 }
 
@@ -0,0 +1,803 @@
+// Quadratic funding rounds run by the factory.
+//
+// A round holds a matching pool in one token, admits campaigns from the
+// factory registry, and at close splits the pool across them by the
+// quadratic funding rule: a campaign's weight is (Σ √cᵢ)² − Σ cᵢ over its
+// contributors' amounts cᵢ. Allocations are deterministic — integer square
+// roots, floor division, and registration/contributor order — so
+// `preview_round` returns exactly what `finalize_round` would pay at that
+// ledger state.
+//
+// After the round ends its contributions are read in pages by `tally_round`,
+// which keeps each campaign's running Σ √cᵢ and Σ cᵢ, so no single call has
+// to visit every contributor. If a round is never finalized its admin can
+// reclaim the pool once `ROUND_FINALIZE_WINDOW` has passed.
+
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contracterror, contractimpl, contracttype, token, vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+use crate::{FactoryContract, FactoryContractArgs, FactoryContractClient, REGISTRY_KEY};
+
+/// Upper bound on campaigns per round, keeping finalization within budget.
+const MAX_ROUND_CAMPAIGNS: u32 = 50;
+
+/// Most contributions read by a single `tally_round` call.
+const TALLY_PAGE_LIMIT: u32 = 50;
+
+/// Seconds after a round ends before its admin may cancel it unfinalized.
+const ROUND_FINALIZE_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum RoundError {
+    RoundNotFound = 1,
+    InvalidPool = 2,
+    InvalidEndTime = 3,
+    RoundClosed = 4,
+    RoundStillOpen = 5,
+    AlreadyFinalized = 6,
+    UnknownCampaign = 7,
+    DuplicateCampaign = 8,
+    TokenMismatch = 9,
+    TooManyCampaigns = 10,
+    TallyIncomplete = 11,
+    FinalizeWindowOpen = 12,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Round {
+    pub admin: Address,
+    pub token: Address,
+    pub pool: i128,
+    pub end_time: u64,
+    pub campaigns: Vec<Address>,
+    pub finalized: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchAllocation {
+    pub campaign: Address,
+    pub amount: i128,
+}
+
+/// Progress of a round's paged tally: the next contribution to read and
+/// each campaign's running sums so far.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundTally {
+    pub campaign: u32,
+    pub contributor: u32,
+    pub root_sums: Vec<i128>,
+    pub sums: Vec<i128>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum RoundKey {
+    Count,
+    Round(u32),
+    Tally(u32),
+}
+
+#[contractimpl]
+impl FactoryContract {
+    /// Open a quadratic funding round, moving `pool` of `token` from `admin`
+    /// into the factory. Returns the new round id.
+    pub fn create_round(
+        env: Env,
+        admin: Address,
+        token: Address,
+        pool: i128,
+        end_time: u64,
+    ) -> Result<u32, RoundError> {
+        admin.require_auth();
+
+        if pool <= 0 {
+            return Err(RoundError::InvalidPool);
+        }
+        if end_time <= env.ledger().timestamp() {
+            return Err(RoundError::InvalidEndTime);
+        }
+
+        token::Client::new(&env, &token).transfer(&admin, &env.current_contract_address(), &pool);
+
+        let round_id: u32 = env
+            .storage()
+            .persistent()
+            .get(&RoundKey::Count)
+            .unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&RoundKey::Count, &(round_id + 1));
+        env.storage().persistent().set(
+            &RoundKey::Round(round_id),
+            &Round {
+                admin: admin.clone(),
+                token,
+                pool,
+                end_time,
+                campaigns: Vec::new(&env),
+                finalized: false,
+            },
+        );
+
+        env.events().publish(
+            ("factory", "round_created"),
+            (round_id, admin, pool, end_time),
+        );
+
+        Ok(round_id)
+    }
+
+    /// Register a factory-deployed campaign in an open round — requires the
+    /// round admin. The campaign must raise in the round's token, and must
+    /// list the factory through its `add_grant_source` to receive a match;
+    /// a refused match goes back to the admin at finalization.
+    pub fn add_round_campaign(
+        env: Env,
+        round_id: u32,
+        campaign: Address,
+    ) -> Result<(), RoundError> {
+        let mut round = load_round(&env, round_id)?;
+        round.admin.require_auth();
+
+        if round.finalized || env.ledger().timestamp() > round.end_time {
+            return Err(RoundError::RoundClosed);
+        }
+        let registry: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&Symbol::new(&env, REGISTRY_KEY))
+            .unwrap_or(Vec::new(&env));
+        if !registry.contains(&campaign) {
+            return Err(RoundError::UnknownCampaign);
+        }
+        if round.campaigns.contains(&campaign) {
+            return Err(RoundError::DuplicateCampaign);
+        }
+        if round.campaigns.len() >= MAX_ROUND_CAMPAIGNS {
+            return Err(RoundError::TooManyCampaigns);
+        }
+        let campaign_token: Address =
+            env.invoke_contract(&campaign, &Symbol::new(&env, "token"), vec![&env]);
+        if campaign_token != round.token {
+            return Err(RoundError::TokenMismatch);
+        }
+
+        round.campaigns.push_back(campaign.clone());
+        env.storage()
+            .persistent()
+            .set(&RoundKey::Round(round_id), &round);
+
+        env.events()
+            .publish(("factory", "round_campaign_added"), (round_id, campaign));
+
+        Ok(())
+    }
+
+    /// Returns a round, if it exists.
+    pub fn get_round(env: Env, round_id: u32) -> Option<Round> {
+        env.storage().persistent().get(&RoundKey::Round(round_id))
+    }
+
+    /// Returns the match each campaign would receive if the round were
+    /// finalized against the current contributions.
+    pub fn preview_round(env: Env, round_id: u32) -> Result<Vec<MatchAllocation>, RoundError> {
+        let round = load_round(&env, round_id)?;
+        Ok(compute_allocations(&env, &round))
+    }
+
+    /// Read up to `limit` more contributions of an ended round into its
+    /// tally. Anyone may call this, repeatedly, until it returns true.
+    ///
+    /// Pages hold at most `TALLY_PAGE_LIMIT` contributions. A campaign whose
+    /// contributions cannot be read counts as having none.
+    pub fn tally_round(env: Env, round_id: u32, limit: u32) -> Result<bool, RoundError> {
+        let round = load_round(&env, round_id)?;
+        if round.finalized {
+            return Err(RoundError::AlreadyFinalized);
+        }
+        if env.ledger().timestamp() <= round.end_time {
+            return Err(RoundError::RoundStillOpen);
+        }
+
+        let mut tally = load_tally(&env, round_id, &round);
+        let mut budget = limit.min(TALLY_PAGE_LIMIT);
+        while budget > 0 && tally.campaign < round.campaigns.len() {
+            let campaign = round.campaigns.get(tally.campaign).unwrap();
+            let contributors = read_contributors(&env, &campaign);
+            let mut root_sum = tally.root_sums.get(tally.campaign).unwrap();
+            let mut sum = tally.sums.get(tally.campaign).unwrap();
+            while budget > 0 && tally.contributor < contributors.len() {
+                let contributor = contributors.get(tally.contributor).unwrap();
+                let amount = read_contribution(&env, &campaign, &contributor);
+                if amount > 0 {
+                    root_sum += isqrt(amount);
+                    sum = sum.checked_add(amount).expect("quadratic weight overflow");
+                }
+                tally.contributor += 1;
+                budget -= 1;
+            }
+            tally.root_sums.set(tally.campaign, root_sum);
+            tally.sums.set(tally.campaign, sum);
+            if tally.contributor >= contributors.len() {
+                tally.campaign += 1;
+                tally.contributor = 0;
+            }
+        }
+
+        let done = tally.campaign >= round.campaigns.len();
+        env.storage()
+            .persistent()
+            .set(&RoundKey::Tally(round_id), &tally);
+
+        env.events().publish(
+            ("factory", "round_tallied"),
+            (round_id, tally.campaign, done),
+        );
+
+        Ok(done)
+    }
+
+    /// Returns how far a round's tally has got, if it has started.
+    pub fn round_tally(env: Env, round_id: u32) -> Option<RoundTally> {
+        env.storage().persistent().get(&RoundKey::Tally(round_id))
+    }
+
+    /// Close a round whose tally is complete and pay each campaign its match
+    /// as a grant. Anyone may call this.
+    ///
+    /// A campaign that no longer accepts grants (refunded, cancelled or
+    /// already withdrawn) receives nothing; its share and rounding dust go
+    /// back to the round admin. Returns the amounts actually paid.
+    pub fn finalize_round(env: Env, round_id: u32) -> Result<Vec<MatchAllocation>, RoundError> {
+        let mut round = load_round(&env, round_id)?;
+        if round.finalized {
+            return Err(RoundError::AlreadyFinalized);
+        }
+        if env.ledger().timestamp() <= round.end_time {
+            return Err(RoundError::RoundStillOpen);
+        }
+        let tally = load_tally(&env, round_id, &round);
+        if tally.campaign < round.campaigns.len() {
+            return Err(RoundError::TallyIncomplete);
+        }
+
+        round.finalized = true;
+        env.storage()
+            .persistent()
+            .set(&RoundKey::Round(round_id), &round);
+        env.storage()
+            .persistent()
+            .remove(&RoundKey::Tally(round_id));
+
+        let shares = split_pool(&env, round.pool, &tally.root_sums, &tally.sums);
+        let factory = env.current_contract_address();
+        let mut paid = Vec::new(&env);
+        let mut distributed: i128 = 0;
+        for (campaign, share) in round.campaigns.iter().zip(shares.iter()) {
+            let amount = if share > 0 && send_grant(&env, &round.token, &campaign, share) {
+                share
+            } else {
+                0
+            };
+            distributed += amount;
+            paid.push_back(MatchAllocation { campaign, amount });
+        }
+
+        let leftover = round.pool - distributed;
+        if leftover > 0 {
+            token::Client::new(&env, &round.token).transfer(&factory, &round.admin, &leftover);
+        }
+
+        env.events().publish(
+            ("factory", "round_finalized"),
+            (round_id, distributed, leftover),
+        );
+
+        Ok(paid)
+    }
+
+    /// Cancel a round that was not finalized within `ROUND_FINALIZE_WINDOW`
+    /// of its end, returning the whole pool to the admin — requires the
+    /// round admin.
+    pub fn cancel_round(env: Env, round_id: u32) -> Result<(), RoundError> {
+        let mut round = load_round(&env, round_id)?;
+        round.admin.require_auth();
+
+        if round.finalized {
+            return Err(RoundError::AlreadyFinalized);
+        }
+        if env.ledger().timestamp() <= round.end_time.saturating_add(ROUND_FINALIZE_WINDOW) {
+            return Err(RoundError::FinalizeWindowOpen);
+        }
+
+        round.finalized = true;
+        env.storage()
+            .persistent()
+            .set(&RoundKey::Round(round_id), &round);
+        env.storage()
+            .persistent()
+            .remove(&RoundKey::Tally(round_id));
+
+        token::Client::new(&env, &round.token).transfer(
+            &env.current_contract_address(),
+            &round.admin,
+            &round.pool,
+        );
+
+        env.events()
+            .publish(("factory", "round_cancelled"), (round_id, round.pool));
+
+        Ok(())
+    }
+}
+
+fn load_round(env: &Env, round_id: u32) -> Result<Round, RoundError> {
+    env.storage()
+        .persistent()
+        .get(&RoundKey::Round(round_id))
+        .ok_or(RoundError::RoundNotFound)
+}
+
+/// Returns a round's tally, or a fresh one if it has not started.
+fn load_tally(env: &Env, round_id: u32, round: &Round) -> RoundTally {
+    env.storage()
+        .persistent()
+        .get(&RoundKey::Tally(round_id))
+        .unwrap_or_else(|| {
+            let mut zeros = Vec::new(env);
+            for _ in 0..round.campaigns.len() {
+                zeros.push_back(0i128);
+            }
+            RoundTally {
+                campaign: 0,
+                contributor: 0,
+                root_sums: zeros.clone(),
+                sums: zeros,
+            }
+        })
+}
+
+/// Returns a campaign's contributors, or none if it cannot report them.
+fn read_contributors(env: &Env, campaign: &Address) -> Vec<Address> {
+    match env.try_invoke_contract::<Vec<Address>, soroban_sdk::Error>(
+        campaign,
+        &Symbol::new(env, "contributors"),
+        vec![env],
+    ) {
+        Ok(Ok(contributors)) => contributors,
+        _ => Vec::new(env),
+    }
+}
+
+/// Returns a contributor's amount in a campaign, or 0 if it cannot be read.
+fn read_contribution(env: &Env, campaign: &Address, contributor: &Address) -> i128 {
+    match env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        campaign,
+        &Symbol::new(env, "contribution"),
+        vec![env, contributor.into_val(env)],
+    ) {
+        Ok(Ok(amount)) => amount,
+        _ => 0,
+    }
+}
+
+/// Reads each campaign's per-contributor amounts and splits the pool.
+///
+/// Visits every contributor in one call, so it is meant for off-chain reads.
+fn compute_allocations(env: &Env, round: &Round) -> Vec<MatchAllocation> {
+    let mut contributions = Vec::new(env);
+    for campaign in round.campaigns.iter() {
+        let mut amounts = Vec::new(env);
+        for contributor in read_contributors(env, &campaign).iter() {
+            amounts.push_back(read_contribution(env, &campaign, &contributor));
+        }
+        contributions.push_back(amounts);
+    }
+
+    let shares = quadratic_allocations(env, round.pool, &contributions);
+    let mut allocations = Vec::new(env);
+    for (campaign, amount) in round.campaigns.iter().zip(shares.iter()) {
+        allocations.push_back(MatchAllocation { campaign, amount });
+    }
+    allocations
+}
+
+/// Splits `pool` by quadratic funding weight, one entry per campaign.
+///
+/// Each weight is (Σ isqrt(cᵢ))² − Σ cᵢ, floored at zero, and each share is
+/// `pool * weight / total_weight` rounded down, so the shares never sum to
+/// more than `pool`.
+pub(crate) fn quadratic_allocations(
+    env: &Env,
+    pool: i128,
+    contributions: &Vec<Vec<i128>>,
+) -> Vec<i128> {
+    let mut root_sums = Vec::new(env);
+    let mut sums = Vec::new(env);
+    for amounts in contributions.iter() {
+        let mut root_sum: i128 = 0;
+        let mut sum: i128 = 0;
+        for amount in amounts.iter().filter(|amount| *amount > 0) {
+            root_sum += isqrt(amount);
+            sum += amount;
+        }
+        root_sums.push_back(root_sum);
+        sums.push_back(sum);
+    }
+    split_pool(env, pool, &root_sums, &sums)
+}
+
+/// Splits `pool` by the weights (Σ √cᵢ)² − Σ cᵢ given each campaign's
+/// `root_sums` and `sums`.
+fn split_pool(env: &Env, pool: i128, root_sums: &Vec<i128>, sums: &Vec<i128>) -> Vec<i128> {
+    let mut weights = Vec::new(env);
+    let mut total_weight: i128 = 0;
+    for (root_sum, sum) in root_sums.iter().zip(sums.iter()) {
+        let weight = (root_sum
+            .checked_mul(root_sum)
+            .expect("quadratic weight overflow")
+            - sum)
+            .max(0);
+        total_weight = total_weight
+            .checked_add(weight)
+            .expect("quadratic weight overflow");
+        weights.push_back(weight);
+    }
+
+    let mut shares = Vec::new(env);
+    for weight in weights.iter() {
+        let share = if total_weight == 0 {
+            0
+        } else {
+            pool.checked_mul(weight).expect("allocation overflow") / total_weight
+        };
+        shares.push_back(share);
+    }
+    shares
+}
+
+/// Floor of the square root of `n`; zero for non-positive input.
+pub(crate) fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (n >> 1) + (n & 1);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Pays `amount` to `campaign` through its `add_grant` entrypoint, which
+/// pulls the tokens from the factory. Returns false if the campaign refused.
+fn send_grant(env: &Env, token: &Address, campaign: &Address, amount: i128) -> bool {
+    let factory = env.current_contract_address();
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (factory.clone(), campaign.clone(), amount).into_val(env),
+            },
+            sub_invocations: Vec::new(env),
+        }),
+    ]);
+
+    let result = env.try_invoke_contract::<(), soroban_sdk::Error>(
+        campaign,
+        &Symbol::new(env, "add_grant"),
+        vec![env, factory.into_val(env), amount.into_val(env)],
+    );
+    let accepted = matches!(result, Ok(Ok(())));
+    if !accepted {
+        env.events().publish(
+            ("factory", "round_grant_failed"),
+            (campaign.clone(), amount),
+        );
+    }
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{
+        contract, map,
+        testutils::{Address as _, Ledger},
+        vec, BytesN, Env, Map,
+    };
+
+    /// Stands in for a crowdfund campaign: reports fixed contributions and
+    /// pulls grants from the funder, unless told to refuse them.
+    #[contract]
+    struct MockCampaign;
+
+    #[contracttype]
+    #[derive(Clone)]
+    enum MockKey {
+        Token,
+        Contributions,
+        Refuse,
+    }
+
+    #[contractimpl]
+    impl MockCampaign {
+        pub fn setup(env: Env, token: Address, contributions: Map<Address, i128>, refuse: bool) {
+            let storage = env.storage().instance();
+            storage.set(&MockKey::Token, &token);
+            storage.set(&MockKey::Contributions, &contributions);
+            storage.set(&MockKey::Refuse, &refuse);
+        }
+
+        pub fn token(env: Env) -> Address {
+            env.storage().instance().get(&MockKey::Token).unwrap()
+        }
+
+        pub fn contributors(env: Env) -> Vec<Address> {
+            let contributions: Map<Address, i128> = env
+                .storage()
+                .instance()
+                .get(&MockKey::Contributions)
+                .unwrap();
+            contributions.keys()
+        }
+
+        pub fn contribution(env: Env, contributor: Address) -> i128 {
+            let contributions: Map<Address, i128> = env
+                .storage()
+                .instance()
+                .get(&MockKey::Contributions)
+                .unwrap();
+            contributions.get(contributor).unwrap_or(0)
+        }
+
+        pub fn add_grant(env: Env, funder: Address, amount: i128) {
+            let refuse: bool = env.storage().instance().get(&MockKey::Refuse).unwrap();
+            if refuse {
+                panic!("grants not accepted");
+            }
+            funder.require_auth();
+            let token = Self::token(env.clone());
+            token::Client::new(&env, &token).transfer(
+                &funder,
+                &env.current_contract_address(),
+                &amount,
+            );
+        }
+    }
+
+    struct RoundSetup<'a> {
+        env: Env,
+        factory: FactoryContractClient<'a>,
+        token: token::Client<'a>,
+        admin: Address,
+        campaigns: Vec<Address>,
+    }
+
+    /// Registers three mock campaigns backed by four, two and two backers of
+    /// 100 each — weights 1,200, 200 and 200 — the last refusing grants.
+    fn setup_round() -> RoundSetup<'static> {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let token_admin = Address::generate(&env);
+        let token_address = env
+            .register_stellar_asset_contract_v2(token_admin)
+            .address();
+        let factory_address =
+            env.register(FactoryContract, (BytesN::from_array(&env, &[0u8; 32]),));
+
+        let mut campaigns = Vec::new(&env);
+        for (backers, refuse) in [(4, false), (2, false), (2, true)] {
+            let mut contributions = map![&env];
+            for _ in 0..backers {
+                contributions.set(Address::generate(&env), 100i128);
+            }
+            let campaign = env.register(MockCampaign, ());
+            MockCampaignClient::new(&env, &campaign).setup(&token_address, &contributions, &refuse);
+            campaigns.push_back(campaign);
+        }
+        env.as_contract(&factory_address, || {
+            env.storage()
+                .persistent()
+                .set(&Symbol::new(&env, REGISTRY_KEY), &campaigns);
+        });
+
+        let admin = Address::generate(&env);
+        token::StellarAssetClient::new(&env, &token_address).mint(&admin, &1_001);
+
+        RoundSetup {
+            factory: FactoryContractClient::new(&env, &factory_address),
+            token: token::Client::new(&env, &token_address),
+            env,
+            admin,
+            campaigns,
+        }
+    }
+
+    #[test]
+    fn test_round_pays_quadratic_matches_and_returns_refused_share() {
+        let s = setup_round();
+        let end_time = s.env.ledger().timestamp() + 100;
+        let round_id = s
+            .factory
+            .create_round(&s.admin, &s.token.address, &1_001, &end_time);
+        for campaign in s.campaigns.iter() {
+            s.factory.add_round_campaign(&round_id, &campaign);
+        }
+
+        // 1,001 * 1,200 / 1,600 = 750; 1,001 * 200 / 1,600 = 125.
+        let preview = s.factory.preview_round(&round_id);
+        for (allocation, expected) in preview.iter().zip([750, 125, 125]) {
+            assert_eq!(allocation.amount, expected);
+        }
+
+        assert_eq!(
+            s.factory.try_finalize_round(&round_id),
+            Err(Ok(RoundError::RoundStillOpen))
+        );
+        s.env.ledger().set_timestamp(end_time + 1);
+        assert_eq!(
+            s.factory.try_finalize_round(&round_id),
+            Err(Ok(RoundError::TallyIncomplete))
+        );
+        assert!(s.factory.tally_round(&round_id, &50));
+        let paid = s.factory.finalize_round(&round_id);
+
+        assert_eq!(paid.get(0).unwrap().amount, 750);
+        assert_eq!(paid.get(1).unwrap().amount, 125);
+        assert_eq!(paid.get(2).unwrap().amount, 0);
+        assert_eq!(s.token.balance(&s.campaigns.get(0).unwrap()), 750);
+        assert_eq!(s.token.balance(&s.campaigns.get(1).unwrap()), 125);
+        // The refused share plus rounding dust goes back to the admin.
+        assert_eq!(s.token.balance(&s.admin), 126);
+        assert_eq!(s.token.balance(&s.factory.address), 0);
+        assert_eq!(
+            s.factory.try_finalize_round(&round_id),
+            Err(Ok(RoundError::AlreadyFinalized))
+        );
+    }
+
+    #[test]
+    fn test_round_tally_runs_in_pages() {
+        let s = setup_round();
+        let end_time = s.env.ledger().timestamp() + 100;
+        let round_id = s
+            .factory
+            .create_round(&s.admin, &s.token.address, &1_001, &end_time);
+        for campaign in s.campaigns.iter() {
+            s.factory.add_round_campaign(&round_id, &campaign);
+        }
+        s.env.ledger().set_timestamp(end_time + 1);
+
+        // Eight contributions, three per page.
+        assert!(!s.factory.tally_round(&round_id, &3));
+        let tally = s.factory.round_tally(&round_id).unwrap();
+        assert_eq!(tally.campaign, 0);
+        assert_eq!(tally.contributor, 3);
+        assert_eq!(tally.root_sums.get(0).unwrap(), 30);
+        assert!(!s.factory.tally_round(&round_id, &3));
+        assert_eq!(
+            s.factory.try_finalize_round(&round_id),
+            Err(Ok(RoundError::TallyIncomplete))
+        );
+        assert!(s.factory.tally_round(&round_id, &3));
+
+        let paid = s.factory.finalize_round(&round_id);
+        assert_eq!(paid.get(0).unwrap().amount, 750);
+        assert_eq!(paid.get(1).unwrap().amount, 125);
+        assert_eq!(s.factory.round_tally(&round_id), None);
+    }
+
+    #[test]
+    fn test_admin_cancels_unfinalized_round_after_window() {
+        let s = setup_round();
+        let end_time = s.env.ledger().timestamp() + 100;
+        let round_id = s
+            .factory
+            .create_round(&s.admin, &s.token.address, &1_001, &end_time);
+        s.factory
+            .add_round_campaign(&round_id, &s.campaigns.get(0).unwrap());
+
+        s.env.ledger().set_timestamp(end_time + 1);
+        s.factory.tally_round(&round_id, &1);
+        assert_eq!(
+            s.factory.try_cancel_round(&round_id),
+            Err(Ok(RoundError::FinalizeWindowOpen))
+        );
+
+        s.env
+            .ledger()
+            .set_timestamp(end_time + ROUND_FINALIZE_WINDOW + 1);
+        s.factory.cancel_round(&round_id);
+        assert_eq!(s.token.balance(&s.admin), 1_001);
+        assert_eq!(s.token.balance(&s.factory.address), 0);
+        assert!(s.factory.get_round(&round_id).unwrap().finalized);
+        assert_eq!(
+            s.factory.try_finalize_round(&round_id),
+            Err(Ok(RoundError::AlreadyFinalized))
+        );
+    }
+
+    #[test]
+    fn test_round_rejects_unregistered_and_duplicate_campaigns() {
+        let s = setup_round();
+        let end_time = s.env.ledger().timestamp() + 100;
+        let round_id = s
+            .factory
+            .create_round(&s.admin, &s.token.address, &1_000, &end_time);
+
+        let outsider = Address::generate(&s.env);
+        assert_eq!(
+            s.factory.try_add_round_campaign(&round_id, &outsider),
+            Err(Ok(RoundError::UnknownCampaign))
+        );
+
+        let campaign = s.campaigns.get(0).unwrap();
+        s.factory.add_round_campaign(&round_id, &campaign);
+        assert_eq!(
+            s.factory.try_add_round_campaign(&round_id, &campaign),
+            Err(Ok(RoundError::DuplicateCampaign))
+        );
+
+        s.env.ledger().set_timestamp(end_time + 1);
+        assert_eq!(
+            s.factory
+                .try_add_round_campaign(&round_id, &s.campaigns.get(1).unwrap()),
+            Err(Ok(RoundError::RoundClosed))
+        );
+    }
+
+    #[test]
+    fn test_isqrt_floors() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(-4), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(2), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(1_000_000_000_000), 1_000_000);
+    }
+
+    #[test]
+    fn test_broad_support_outweighs_single_whale() {
+        let env = Env::default();
+        // Campaign A: four backers of 100. Campaign B: one backer of 400.
+        let contributions = vec![
+            &env,
+            vec![&env, 100, 100, 100, 100],
+            vec![&env, 400],
+            vec![&env],
+        ];
+        let shares = quadratic_allocations(&env, 10_000, &contributions);
+        // A: (4 * 10)² - 400 = 1,200. B: 20² - 400 = 0.
+        assert_eq!(shares, vec![&env, 10_000, 0, 0]);
+    }
+
+    #[test]
+    fn test_allocations_round_down_within_pool() {
+        let env = Env::default();
+        let contributions = vec![&env, vec![&env, 100, 100], vec![&env, 100, 100, 100]];
+        // Weights 200 and 600.
+        let shares = quadratic_allocations(&env, 1_001, &contributions);
+        assert_eq!(shares, vec![&env, 250, 750]);
+    }
+
+    #[test]
+    fn test_no_weight_allocates_nothing() {
+        let env = Env::default();
+        let contributions = vec![&env, vec![&env, 500], vec![&env, 0, 0]];
+        let shares = quadratic_allocations(&env, 1_000, &contributions);
+        assert_eq!(shares, vec![&env, 0, 0]);
+    }
+}